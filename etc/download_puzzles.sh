@@ -25,9 +25,7 @@ download_nikoli() {
     local NUM="$1"
     local BASEURL="http://www.nikoli.com/nfp"
 
-    curl -s "${BASEURL}/sl-${NUM}.nfp" |
-        sed 's/&/\'$'\n''/g' | sed -n 's/^dataQuestion=//p' | ${ETCDIR}/urldecode.py |
-        sed 's/+//g'
+    curl -s "${BASEURL}/sl-${NUM}.nfp"
 }
 
 main() {
@@ -8,90 +8,158 @@
 
 use std::fs::File;
 use std::io::prelude::*;
+use std::path::Path;
 use time;
 use rustc_test::{Bencher, DynBenchFn, DynTestName, ShouldPanic, TDynBenchFn, TestDesc,
                  TestDescAndFn, self as test};
 
-use srither_core::puzzle::Puzzle;
-use srither_solver::{Solutions, self as solver};
+use srither_core::collection;
+use srither_core::puzzle::ParseMode;
+use srither_solver::{BoardSolutions, Solutions, self as solver};
 
 use error::AppResult;
+use input;
 use parse_arg::BenchConfig;
 
+// The source text of the entry is parsed again in each iteration, as a part of
+// the benchmark. The entries of a collection are benched one by one, each of
+// which is parsed from its own text, and a board of a tiling is benched as a
+// whole.
 struct BenchFn {
-    input: String,
+    name: String,
+    text: String,
+    parse_mode: ParseMode,
     derive_all: bool,
 }
 
 impl TDynBenchFn for BenchFn {
     fn run(&self, harness: &mut Bencher) {
-        harness.iter(|| solve(&self.input, self.derive_all))
+        harness.iter(|| self.solve())
     }
 }
 
 impl BenchFn {
-    fn new(input: String, derive_all: bool) -> BenchFn {
+    fn new(name: String, text: String, parse_mode: ParseMode, derive_all: bool) -> BenchFn {
         BenchFn {
-            input: input,
+            name: name,
+            text: text,
+            parse_mode: parse_mode,
             derive_all: derive_all,
         }
     }
+
+    fn solve(&self) -> AppResult<()> {
+        if let Some(board) = try!(input::parse_board(&self.name, &self.text)) {
+            if self.derive_all {
+                for solution in try!(BoardSolutions::new(&board)) {
+                    let _ = test::black_box(solution);
+                }
+            } else {
+                let _ = test::black_box(try!(solver::solve_board(&board)));
+            }
+            return Ok(());
+        }
+
+        let problems = try!(input::parse_problems(&mut self.text.as_bytes(), self.parse_mode));
+        let puzzle = &problems[0].puzzle;
+
+        if self.derive_all {
+            for solution in try!(Solutions::new(puzzle)) {
+                let _ = test::black_box(solution);
+            }
+        } else {
+            let _ = test::black_box(try!(solver::solve(puzzle)));
+        }
+
+        Ok(())
+    }
 }
 
 pub fn run(config: BenchConfig) -> AppResult<()> {
-    let derive_all = config.derive_all;
-    let inputs = if let Some(n) = config.only_hardest {
-        take_hardest(config.input_files, n, derive_all)
+    let mut benches = vec![];
+    for input in &config.input_files {
+        let text = if Path::new(input).is_file() {
+            let mut buf = String::new();
+            let _ = try!(try!(File::open(input)).read_to_string(&mut buf));
+            buf
+        } else {
+            // The puzz.link URLs and the Loopy game IDs are the texts themselves.
+            input.clone()
+        };
+        if try!(input::parse_board(input, &text)).is_some() {
+            let bench = BenchFn::new(input.clone(), text, config.parse_mode, config.derive_all);
+            benches.push((input.clone(), bench));
+            continue;
+        }
+
+        let problems = try!(input::read_problems(input, config.parse_mode));
+        let texts = if collection::is_collection(&text) {
+            split_entries(&text)
+        } else {
+            vec![text]
+        };
+        for (problem, text) in problems.into_iter().zip(texts) {
+            let bench = BenchFn::new(problem.name.clone(),
+                                     text,
+                                     config.parse_mode,
+                                     config.derive_all);
+            benches.push((problem.name, bench));
+        }
+    }
+    let benches = if let Some(n) = config.only_hardest {
+        take_hardest(benches, n)
     } else {
-        config.input_files
+        benches
     };
-    let tests = inputs.into_iter()
-                      .map(|input| {
-                          TestDescAndFn {
-                              desc: TestDesc {
-                                  name: DynTestName(input.clone()),
-                                  ignore: false,
-                                  should_panic: ShouldPanic::No,
-                              },
-                              testfn: DynBenchFn(Box::new(BenchFn::new(input, derive_all))),
-                          }
-                      })
-                      .collect();
+    let tests = benches.into_iter()
+                       .map(|(name, bench)| {
+                           TestDescAndFn {
+                               desc: TestDesc {
+                                   name: DynTestName(name),
+                                   ignore: false,
+                                   should_panic: ShouldPanic::No,
+                               },
+                               testfn: DynBenchFn(Box::new(bench)),
+                           }
+                       })
+                       .collect();
 
     test::test_main(&["".to_owned(), "--bench".to_owned()], tests);
 
     Ok(())
 }
 
-fn get_elapse(input: &str, derive_all: bool) -> u64 {
-    let start = time::precise_time_ns();
-    let _ = test::black_box(solve(input, derive_all));
-    time::precise_time_ns() - start
+// Splits the collection into the texts of its entries, each of which starts
+// with the line of the name and is a collection of the entry alone. The
+// comments before the first entry are dropped.
+fn split_entries(text: &str) -> Vec<String> {
+    let mut entries = vec![];
+    for line in text.lines() {
+        // A line is the name of an entry if it alone looks like a collection.
+        if collection::is_collection(line) {
+            entries.push(String::new());
+        }
+        if let Some(entry) = entries.last_mut() {
+            entry.push_str(line);
+            entry.push('\n');
+        }
+    }
+    entries
 }
 
-fn take_hardest(inputs: Vec<String>, n: usize, derive_all: bool) -> Vec<String> {
-    let mut inputs = inputs.into_iter()
-                           .map(|input| (get_elapse(&input, derive_all), input))
-                           .collect::<Vec<_>>();
-    inputs.sort_by(|a, b| a.cmp(b).reverse());
-    inputs.into_iter()
-          .map(|pair| pair.1)
-          .take(n)
-          .collect()
+fn get_elapse(bench: &BenchFn) -> u64 {
+    let start = time::precise_time_ns();
+    let _ = test::black_box(bench.solve());
+    time::precise_time_ns() - start
 }
 
-fn solve(file: &str, derive_all: bool) -> AppResult<()> {
-    let mut buf = String::new();
-    let _ = try!(try!(File::open(file)).read_to_string(&mut buf));
-    let puzzle = try!(buf.parse::<Puzzle>());
-
-    if derive_all {
-        for solution in try!(Solutions::new(&puzzle)) {
-            let _ = test::black_box(solution);
-        }
-    } else {
-        let _ = test::black_box(try!(solver::solve(&puzzle)));
-    }
-
-    Ok(())
+fn take_hardest(benches: Vec<(String, BenchFn)>, n: usize) -> Vec<(String, BenchFn)> {
+    let mut benches = benches.into_iter()
+                             .map(|bench| (get_elapse(&bench.1), bench))
+                             .collect::<Vec<_>>();
+    benches.sort_by(|a, b| a.0.cmp(&b.0).reverse());
+    benches.into_iter()
+           .map(|pair| pair.1)
+           .take(n)
+           .collect()
 }
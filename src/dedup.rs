@@ -0,0 +1,81 @@
+// Copyright (c) 2016 srither developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+use srither_core::puzzle::ParseMode;
+
+use error::AppResult;
+use input::{self, SOLUTION_EXTENSION};
+use parse_arg::DedupConfig;
+
+pub fn run(config: DedupConfig) -> AppResult<()> {
+    let mut files = vec![];
+    for input in &config.inputs {
+        try!(collect_files(Path::new(input), &mut files));
+    }
+    files.sort();
+
+    let mut groups = BTreeMap::new();
+    for file in files {
+        let name = file.to_string_lossy().into_owned();
+        // Files which are not puzzles are skipped, as the directories may
+        // contain other files such as the saved pages.
+        let problems = match input::read_problems(&name, ParseMode::Lenient) {
+            Ok(problems) => problems,
+            Err(e) => {
+                let _ = writeln!(&mut io::stderr(), "skipping {}: {}", name, e);
+                continue;
+            }
+        };
+        for problem in problems {
+            groups.entry(problem.puzzle.fingerprint())
+                  .or_insert_with(Vec::new)
+                  .push(problem.name);
+        }
+    }
+
+    let mut first = true;
+    for (fingerprint, names) in groups {
+        if names.len() < 2 {
+            continue;
+        }
+        if !first {
+            println!("");
+        }
+        first = false;
+        println!("{:016x}", fingerprint);
+        for name in names {
+            println!("  {}", name);
+        }
+    }
+
+    Ok(())
+}
+
+// The solutions written next to the puzzles are not collected.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> AppResult<()> {
+    if !path.is_dir() {
+        files.push(path.to_owned());
+        return Ok(());
+    }
+
+    for entry in try!(fs::read_dir(path)) {
+        let path = try!(entry).path();
+        if path.is_dir() {
+            try!(collect_files(&path, files));
+        } else if path.extension().and_then(|ext| ext.to_str()) != Some(SOLUTION_EXTENSION) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -8,17 +8,32 @@
 
 use std::{fmt, io};
 use std::error::Error;
+use serde_json;
 use term;
 
-use srither_core::puzzle::ParsePuzzleError;
+use srither_core::collection::ParseCollectionError;
+use srither_core::janko::ParseJankoError;
+use srither_core::loopy::{EncodeLoopyError, ParseLoopyError};
+use srither_core::puzzle::{LoopPathError, ParsePuzzleError};
+use srither_core::pzpr::{EncodePzprError, ParsePzprError};
 use srither_solver as solver;
 
 #[derive(Debug)]
 pub enum AppError {
     Io(io::Error),
     Term(term::Error),
+    Json(serde_json::Error),
     ParsePuzzle(ParsePuzzleError),
+    ParsePzpr(ParsePzprError),
+    ParseJanko(ParseJankoError),
+    ParseLoopy(ParseLoopyError),
+    EncodePzpr(EncodePzprError),
+    EncodeLoopy(EncodeLoopyError),
+    ParseCollection(ParseCollectionError),
+    Diagnostics(String),
+    LoopPath(LoopPathError),
     Solver(solver::Error),
+    Unsupported(&'static str),
 }
 
 impl From<io::Error> for AppError {
@@ -33,12 +48,60 @@ impl From<term::Error> for AppError {
     }
 }
 
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> AppError {
+        AppError::Json(err)
+    }
+}
+
 impl From<ParsePuzzleError> for AppError {
     fn from(err: ParsePuzzleError) -> AppError {
         AppError::ParsePuzzle(err)
     }
 }
 
+impl From<ParsePzprError> for AppError {
+    fn from(err: ParsePzprError) -> AppError {
+        AppError::ParsePzpr(err)
+    }
+}
+
+impl From<ParseJankoError> for AppError {
+    fn from(err: ParseJankoError) -> AppError {
+        AppError::ParseJanko(err)
+    }
+}
+
+impl From<ParseLoopyError> for AppError {
+    fn from(err: ParseLoopyError) -> AppError {
+        AppError::ParseLoopy(err)
+    }
+}
+
+impl From<EncodePzprError> for AppError {
+    fn from(err: EncodePzprError) -> AppError {
+        AppError::EncodePzpr(err)
+    }
+}
+
+impl From<EncodeLoopyError> for AppError {
+    fn from(err: EncodeLoopyError) -> AppError {
+        AppError::EncodeLoopy(err)
+    }
+}
+
+impl From<ParseCollectionError> for AppError {
+    fn from(err: ParseCollectionError) -> AppError {
+        AppError::ParseCollection(err)
+    }
+}
+
+impl From<LoopPathError> for AppError {
+    fn from(err: LoopPathError) -> AppError {
+        AppError::LoopPath(err)
+    }
+}
+
 impl From<solver::Error> for AppError {
     fn from(err: solver::Error) -> AppError {
         AppError::Solver(err)
@@ -50,16 +113,36 @@ impl Error for AppError {
         match *self {
             AppError::Io(ref e) => e.description(),
             AppError::Term(ref e) => e.description(),
+            AppError::Json(ref e) => e.description(),
             AppError::ParsePuzzle(ref e) => e.description(),
+            AppError::ParsePzpr(ref e) => e.description(),
+            AppError::ParseJanko(ref e) => e.description(),
+            AppError::ParseLoopy(ref e) => e.description(),
+            AppError::EncodePzpr(ref e) => e.description(),
+            AppError::EncodeLoopy(ref e) => e.description(),
+            AppError::ParseCollection(ref e) => e.description(),
+            AppError::Diagnostics(ref s) => s,
+            AppError::LoopPath(ref e) => e.description(),
             AppError::Solver(ref e) => e.description(),
+            AppError::Unsupported(s) => s,
         }
     }
     fn cause(&self) -> Option<&Error> {
         match *self {
             AppError::Io(ref e) => Some(e),
             AppError::Term(ref e) => Some(e),
+            AppError::Json(ref e) => Some(e),
             AppError::ParsePuzzle(ref e) => Some(e),
+            AppError::ParsePzpr(ref e) => Some(e),
+            AppError::ParseJanko(ref e) => Some(e),
+            AppError::ParseLoopy(ref e) => Some(e),
+            AppError::EncodePzpr(ref e) => Some(e),
+            AppError::EncodeLoopy(ref e) => Some(e),
+            AppError::ParseCollection(ref e) => Some(e),
+            AppError::Diagnostics(_) => None,
+            AppError::LoopPath(ref e) => Some(e),
             AppError::Solver(ref e) => Some(e),
+            AppError::Unsupported(_) => None,
         }
     }
 }
@@ -69,8 +152,18 @@ impl fmt::Display for AppError {
         match *self {
             AppError::Io(ref e) => write!(f, "IO error: {}", e),
             AppError::Term(ref e) => write!(f, "terminal error: {}", e),
+            AppError::Json(ref e) => write!(f, "JSON error: {}", e),
             AppError::ParsePuzzle(ref e) => write!(f, "parse puzzle error: {}", e),
+            AppError::ParsePzpr(ref e) => write!(f, "parse pzprjs URL error: {}", e),
+            AppError::ParseJanko(ref e) => write!(f, "parse janko.at page error: {}", e),
+            AppError::ParseLoopy(ref e) => write!(f, "parse Loopy game error: {}", e),
+            AppError::EncodePzpr(ref e) => write!(f, "encode pzprjs error: {}", e),
+            AppError::EncodeLoopy(ref e) => write!(f, "encode Loopy game error: {}", e),
+            AppError::ParseCollection(ref e) => write!(f, "parse collection error: {}", e),
+            AppError::Diagnostics(ref s) => s.fmt(f),
+            AppError::LoopPath(ref e) => write!(f, "loop path error: {}", e),
             AppError::Solver(ref e) => write!(f, "solver error: {}", e),
+            AppError::Unsupported(s) => write!(f, "unsupported: {}", s),
         }
     }
 }
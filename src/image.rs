@@ -0,0 +1,144 @@
+// Copyright (c) 2016 srither developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{cmp, i32};
+use srither_core::puzzle::{Marker, Puzzle};
+use srither_core::geom::{Geom, Point};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Theme {
+    Color,
+    Mono,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Config {
+    pub theme: Theme,
+    pub cell_size: usize,
+    pub shade_inside: bool,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+pub struct Palette {
+    pub background: Rgb,
+    pub inside: Rgb,
+    pub dot: Rgb,
+    pub hint: Rgb,
+    pub line: Rgb,
+    pub cross: Rgb,
+}
+
+pub fn theme_to_palette(theme: Theme) -> Palette {
+    match theme {
+        Theme::Color => {
+            Palette {
+                background: Rgb(0xff, 0xff, 0xff),
+                inside: Rgb(0xff, 0xf0, 0xa0),
+                dot: Rgb(0x00, 0x00, 0x00),
+                hint: Rgb(0x00, 0x00, 0x00),
+                line: Rgb(0x20, 0x40, 0xc0),
+                cross: Rgb(0xc0, 0x30, 0x30),
+            }
+        }
+        Theme::Mono => {
+            Palette {
+                background: Rgb(0xff, 0xff, 0xff),
+                inside: Rgb(0xd8, 0xd8, 0xd8),
+                dot: Rgb(0x00, 0x00, 0x00),
+                hint: Rgb(0x00, 0x00, 0x00),
+                line: Rgb(0x00, 0x00, 0x00),
+                cross: Rgb(0x80, 0x80, 0x80),
+            }
+        }
+    }
+}
+
+// Sizes of the parts of an image, in pixels.
+pub struct Metrics {
+    pub cell: i32,
+    pub margin: i32,
+    pub line_width: i32,
+    pub cross: i32,
+    pub dot: i32,
+    pub vertex_hint: i32,
+}
+
+impl Metrics {
+    pub fn new(conf: &Config) -> Metrics {
+        let cell = conf.cell_size as i32;
+        Metrics {
+            cell: cell,
+            margin: cell / 2,
+            line_width: cmp::max(cell / 8, 1),
+            cross: cmp::max(cell / 10, 1),
+            dot: cmp::max(cell / 16, 1),
+            vertex_hint: cmp::max(cell / 4, 2),
+        }
+    }
+
+    pub fn width(&self, puzzle: &Puzzle) -> i32 {
+        puzzle.column() * self.cell + self.margin * 2
+    }
+
+    pub fn height(&self, puzzle: &Puzzle) -> i32 {
+        puzzle.row() * self.cell + self.margin * 2
+    }
+
+    // The position of the top-left corner of the cell.
+    pub fn pos(&self, p: Point) -> (i32, i32) {
+        (self.margin + p.1 * self.cell, self.margin + p.0 * self.cell)
+    }
+}
+
+// Whether the positions of all the pixels in the image of the puzzle fit in
+// `i32`, which the large `--cell-size` overflows when multiplied by the size
+// of the board.
+pub fn fits(conf: &Config, puzzle: &Puzzle) -> bool {
+    let cell = conf.cell_size as u64;
+    let len = cmp::max(puzzle.row(), puzzle.column()) as u64;
+    cell <= i32::MAX as u64 &&
+    len.checked_mul(cell)
+       .and_then(|n| n.checked_add(cell / 2 * 2))
+       .map_or(false, |n| n <= i32::MAX as u64)
+}
+
+// The text drawn in the cell, which is the hint, the set of the numbers such
+// as `12` or `?`, or the marker.
+pub fn cell_text(puzzle: &Puzzle, p: Point) -> Option<String> {
+    match (puzzle.hint(p), puzzle.hint_set(p), puzzle.marker(p)) {
+        (Some(n), _, _) => Some(n.to_string()),
+        (None, Some(set), _) => Some(set.to_string()),
+        (None, None, Some(Marker::Sheep)) => Some("S".to_owned()),
+        (None, None, Some(Marker::Wolf)) => Some("W".to_owned()),
+        (None, None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fits, Config, Theme};
+    use srither_core::puzzle::Puzzle;
+
+    #[test]
+    fn fits_cell_size() {
+        let puzzle = "__\n__\n__\n".parse::<Puzzle>().unwrap();
+        let conf = |size| {
+            Config {
+                theme: Theme::Color,
+                cell_size: size,
+                shade_inside: true,
+            }
+        };
+        assert!(fits(&conf(32), &puzzle));
+        assert!(fits(&conf(536870911), &puzzle));
+        assert!(!fits(&conf(536870912), &puzzle));
+        assert!(!fits(&conf(1 << 40), &puzzle));
+    }
+}
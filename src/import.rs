@@ -0,0 +1,65 @@
+// Copyright (c) 2016 srither developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+use srither_core::janko;
+
+use error::AppResult;
+use input::SOLUTION_EXTENSION;
+use parse_arg::ImportConfig;
+
+pub fn run(config: ImportConfig) -> AppResult<()> {
+    let mut pages = vec![];
+    for entry in try!(fs::read_dir(&config.input_dir)) {
+        let path = try!(entry).path();
+        if path.is_file() && is_page_file(&path) {
+            pages.push(path);
+        }
+    }
+    pages.sort();
+
+    try!(fs::create_dir_all(&config.output_dir));
+    for page in pages {
+        let dst = output_path(&config.output_dir, &page);
+        try!(import(&page, &dst));
+        println!("{} -> {}", page.display(), dst.display());
+    }
+
+    Ok(())
+}
+
+fn is_page_file(path: &Path) -> bool {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("htm") | Some("html") => true,
+        _ => false,
+    }
+}
+
+// `001.a.htm` is imported as `001.txt`.
+fn output_path(output_dir: &str, page: &Path) -> PathBuf {
+    let name = page.file_name().and_then(|name| name.to_str()).unwrap_or("");
+    let name = name.split('.').next().unwrap_or(name);
+    Path::new(output_dir).join(name).with_extension("txt")
+}
+
+fn import(page: &Path, dst: &Path) -> AppResult<()> {
+    let mut buf = String::new();
+    let _ = try!(try!(File::open(page)).read_to_string(&mut buf));
+    let (puzzle, solution) = try!(janko::parse_page(&buf));
+
+    try!(try!(File::create(dst)).write_all(puzzle.to_string().as_bytes()));
+    if let Some(solution) = solution {
+        let dst = dst.with_extension(SOLUTION_EXTENSION);
+        try!(try!(File::create(dst)).write_all(solution.to_string().as_bytes()));
+    }
+
+    Ok(())
+}
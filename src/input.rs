@@ -0,0 +1,175 @@
+// Copyright (c) 2016 srither developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+
+use srither_core::collection;
+use srither_core::diagnostic::{self, Span};
+use srither_core::graph::{self, Board};
+use srither_core::janko;
+use srither_core::loopy;
+use srither_core::puzzle::{ParseMode, Puzzle};
+use srither_core::pzpr;
+
+use error::{AppError, AppResult};
+
+pub const SOLUTION_EXTENSION: &'static str = "solution";
+
+/// A puzzle to solve, read from a file or from an entry of a collection.
+pub struct Problem {
+    pub name: String,
+    pub entry: Option<String>,
+    pub file_stem: String,
+    pub puzzle: Puzzle,
+    pub solution: Option<Puzzle>,
+}
+
+pub fn read_problems(source: &str, mode: ParseMode) -> AppResult<Vec<Problem>> {
+    if !Path::new(source).exists() {
+        let puzzle = if pzpr::is_url(source) {
+            Some(try!(pzpr::parse_url(source)))
+        } else if loopy::is_game_id(source) {
+            Some(try!(loopy::parse_game_id(source)))
+        } else {
+            None
+        };
+        if let Some(puzzle) = puzzle {
+            return Ok(vec![Problem {
+                               name: source.to_owned(),
+                               entry: None,
+                               file_stem: "solution".to_owned(),
+                               puzzle: puzzle,
+                               solution: None,
+                           }]);
+        }
+    }
+
+    let mut buf = String::new();
+    let _ = try!(try!(File::open(source)).read_to_string(&mut buf));
+    let stem = Path::new(source).file_stem().and_then(|s| s.to_str()).unwrap_or("solution");
+    if collection::is_collection(&buf) {
+        return from_collection(source, stem, &buf, mode);
+    }
+
+    Ok(vec![Problem {
+                name: source.to_owned(),
+                entry: None,
+                file_stem: stem.to_owned(),
+                puzzle: try!(parse_puzzle(source, &buf, mode)),
+                solution: try!(read_solution(source, mode)),
+            }])
+}
+
+pub fn parse_problems<T: Read>(input: &mut T, mode: ParseMode) -> AppResult<Vec<Problem>> {
+    let mut buf = String::new();
+    let _ = try!(input.read_to_string(&mut buf));
+    if collection::is_collection(&buf) {
+        return from_collection("<stdin>", "solution", &buf, mode);
+    }
+
+    Ok(vec![Problem {
+                name: "<stdin>".to_owned(),
+                entry: None,
+                file_stem: "solution".to_owned(),
+                puzzle: try!(parse_puzzle("<stdin>", &buf, mode)),
+                solution: None,
+            }])
+}
+
+/// Reads a board of a tiling, if the file is not a puzzle of the square grid.
+pub fn read_board(source: &str) -> AppResult<Option<Board>> {
+    if !Path::new(source).is_file() {
+        return Ok(None);
+    }
+
+    let mut buf = String::new();
+    let _ = try!(try!(File::open(source)).read_to_string(&mut buf));
+    parse_board(source, &buf)
+}
+
+pub fn parse_board(name: &str, buf: &str) -> AppResult<Option<Board>> {
+    if !graph::is_board(buf) {
+        return Ok(None);
+    }
+    buf.parse()
+       .map(Some)
+       .map_err(|e: graph::ParseBoardError| diagnostics(name, buf, &[(e.description(), e.span())]))
+}
+
+// The entries are named after the file, such as `puzzles.txt:001`.
+fn from_collection(name: &str,
+                   stem: &str,
+                   buf: &str,
+                   mode: ParseMode)
+                   -> AppResult<Vec<Problem>> {
+    let entries = match collection::parse_all_with_mode(buf, mode) {
+        Ok(entries) => entries,
+        Err(errors) => {
+            let errors = errors.iter().map(|e| (e.description(), e.span())).collect::<Vec<_>>();
+            return Err(diagnostics(name, buf, &errors));
+        }
+    };
+    Ok(entries.into_iter()
+              .map(|entry| {
+                  Problem {
+                      name: format!("{}:{}", name, entry.name),
+                      file_stem: format!("{}.{}", stem, entry.name),
+                      entry: Some(entry.name),
+                      puzzle: entry.puzzle,
+                      solution: entry.solution,
+                  }
+              })
+              .collect())
+}
+
+// Renders the errors with the lines of the input they point to.
+fn diagnostics(name: &str, buf: &str, errors: &[(&str, Option<Span>)]) -> AppError {
+    let text = errors.iter()
+                     .map(|&(msg, span)| {
+                         match span {
+                             Some(span) => diagnostic::render(name, buf, span, msg),
+                             None => format!("error: {}\n  --> {}\n", msg, name),
+                         }
+                     })
+                     .collect::<Vec<_>>()
+                     .join("\n");
+    AppError::Diagnostics(text.trim_right().to_owned())
+}
+
+fn parse_puzzle(name: &str, buf: &str, mode: ParseMode) -> AppResult<Puzzle> {
+    if buf.contains("dataQuestion=") {
+        Ok(try!(Puzzle::from_nfp(buf)))
+    } else if loopy::is_save(buf) {
+        Ok(try!(loopy::parse_save(buf)))
+    } else if janko::is_page(buf) {
+        Ok(try!(janko::parse_page(buf)).0)
+    } else if pzpr::is_file(buf) {
+        Ok(try!(pzpr::parse_file(buf)))
+    } else if pzpr::is_url(buf) {
+        Ok(try!(pzpr::parse_url(buf)))
+    } else if loopy::is_game_id(buf) {
+        Ok(try!(loopy::parse_game_id(buf)))
+    } else {
+        Puzzle::parse_with_mode(buf, mode)
+            .map_err(|e| diagnostics(name, buf, &[(e.description(), e.span())]))
+    }
+}
+
+fn read_solution(source: &str, mode: ParseMode) -> AppResult<Option<Puzzle>> {
+    let path = Path::new(source).with_extension(SOLUTION_EXTENSION);
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let mut buf = String::new();
+    let _ = try!(try!(File::open(&path)).read_to_string(&mut buf));
+    Ok(Some(try!(parse_puzzle(&path.to_string_lossy(), &buf, mode))))
+}
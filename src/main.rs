@@ -32,6 +32,7 @@ extern crate term;
 extern crate argparse;
 extern crate test as rustc_test;
 extern crate time;
+extern crate serde_json;
 
 extern crate srither_core;
 extern crate srither_solver;
@@ -43,18 +44,26 @@ use error::AppResult;
 use parse_arg::Config;
 
 mod error;
+mod image;
+mod input;
 mod parse_arg;
+mod png;
 mod pprint;
+mod svg;
 
 mod solve;
 mod test;
 mod bench;
+mod import;
+mod dedup;
 
 fn run() -> AppResult<()> {
     match Config::parse() {
         Config::Solve(config) => solve::run(config),
         Config::Test(config) => test::run(config),
         Config::Bench(config) => bench::run(config),
+        Config::Import(config) => import::run(config),
+        Config::Dedup(config) => dedup::run(config),
     }
 }
 
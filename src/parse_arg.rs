@@ -9,14 +9,19 @@
 use std::{io, process};
 use std::str::FromStr;
 use argparse::{ArgumentParser, List, Store, StoreOption, StoreTrue};
+use srither_core::puzzle::ParseMode;
+use srither_core::tikz::Mode as TikzMode;
 
 use pprint::{self, Config as PpConfig, Mode as PpMode};
+use image::{Config as ImageConfig, Theme as ImageTheme};
 
 #[derive(Copy, Clone, Debug)]
 enum CommandType {
     Solve,
     Test,
     Bench,
+    Import,
+    Dedup,
 }
 
 impl CommandType {
@@ -26,7 +31,7 @@ impl CommandType {
         ap.set_description("Slither link solver - Command line interface");
         let _ = ap.refer(self)
                   .required()
-                  .add_argument("command", Store, "command to run (solve, test)");
+                  .add_argument("command", Store, "command to run (solve, test, bench, import, dedup)");
         let _ = ap.refer(args)
                   .add_argument("arguments", List, "arguments for command");
         ap.stop_on_first_argument(true);
@@ -47,6 +52,8 @@ impl FromStr for CommandType {
             "solve" => Ok(CommandType::Solve),
             "test" => Ok(CommandType::Test),
             "bench" => Ok(CommandType::Bench),
+            "import" => Ok(CommandType::Import),
+            "dedup" => Ok(CommandType::Dedup),
             _ => Err(()),
         }
     }
@@ -62,6 +69,10 @@ struct SolveArgs {
     output_mode: OutputModeArg,
     width: Size,
     height: Size,
+    cell_size: Size,
+    theme: ImageTheme,
+    no_shading: bool,
+    strict: bool,
     input_files: Vec<String>,
 }
 
@@ -73,18 +84,48 @@ impl SetupParser for SolveArgs {
         let _ = ap.refer(&mut self.output_mode)
                   .add_option(&["--output-mode"],
                               Store,
-                              "specify output mode (auto, pretty-color, pretty-ascii, raw, none) \
+                              "specify output mode (auto, pretty-color, pretty-ascii, raw, pzprv3, \
+                               loopy, loopy-save, json, path, svg, png, tikz, tikz-bare, none) \
                                [default: auto]");
         let _ = ap.refer(&mut self.width)
                   .add_option(&["--width"], Store, "specify cell width [default: 2]");
         let _ = ap.refer(&mut self.height)
                   .add_option(&["--height"], Store, "specify cell width [default: 1]");
+        // An image of the cells of 0 pixels has no scanline to encode, so
+        // `--cell-size 0` is rejected as well as the sizes which overflow the
+        // image of the board (see `image::fits`).
+        let _ = ap.refer(&mut self.cell_size)
+                  .add_option(&["--cell-size"],
+                              Store,
+                              "specify cell size of image in pixels [default: 32]");
+        let _ = ap.refer(&mut self.theme)
+                  .add_option(&["--theme"],
+                              Store,
+                              "specify theme of image (color, mono) [default: color]");
+        let _ = ap.refer(&mut self.no_shading)
+                  .add_option(&["--no-shading"],
+                              StoreTrue,
+                              "do not shade the cells inside the loop in image.");
+        let _ = ap.refer(&mut self.strict)
+                  .add_option(&["--strict"],
+                              StoreTrue,
+                              "reject unknown edges and text outside of the lattice.");
         let _ = ap.refer(&mut self.input_files)
-                  .add_argument("input_files", List, "puzzle files to solve.");
+                  .add_argument("input_files",
+                                List,
+                                "puzzle files, puzz.link URLs or Loopy game IDs to solve.");
     }
 }
 
 impl SolveArgs {
+    fn image_config(&self) -> ImageConfig {
+        ImageConfig {
+            theme: self.theme,
+            cell_size: self.cell_size.0,
+            shade_inside: !self.no_shading,
+        }
+    }
+
     fn output_mode(&self) -> OutputMode {
         let ppmode = match self.output_mode {
             OutputModeArg::Auto => {
@@ -97,6 +138,15 @@ impl SolveArgs {
             OutputModeArg::PrettyColor => PpMode::Color,
             OutputModeArg::PrettyAscii => PpMode::Ascii,
             OutputModeArg::Raw => return OutputMode::Raw,
+            OutputModeArg::Pzprv3 => return OutputMode::Pzprv3,
+            OutputModeArg::Loopy => return OutputMode::Loopy,
+            OutputModeArg::LoopySave => return OutputMode::LoopySave,
+            OutputModeArg::Json => return OutputMode::Json,
+            OutputModeArg::Path => return OutputMode::Path,
+            OutputModeArg::Svg => return OutputMode::Svg(self.image_config()),
+            OutputModeArg::Png => return OutputMode::Png(self.image_config()),
+            OutputModeArg::Tikz => return OutputMode::Tikz(TikzMode::Solved),
+            OutputModeArg::TikzBare => return OutputMode::Tikz(TikzMode::Bare),
             OutputModeArg::None => return OutputMode::None,
         };
         OutputMode::Pretty(PpConfig {
@@ -114,6 +164,10 @@ impl Default for SolveArgs {
             output_mode: OutputModeArg::Auto,
             width: Size(2),
             height: Size(1),
+            cell_size: Size(32),
+            theme: ImageTheme::Color,
+            no_shading: false,
+            strict: false,
             input_files: vec![],
         }
     }
@@ -124,11 +178,21 @@ impl Into<Config> for SolveArgs {
         Config::Solve(SolveConfig {
             derive_all: self.derive_all,
             output_mode: self.output_mode(),
+            parse_mode: parse_mode(self.strict),
             input_files: self.input_files,
         })
     }
 }
 
+fn parse_mode(strict: bool) -> ParseMode {
+    if strict {
+        ParseMode::Strict
+    } else {
+        ParseMode::Lenient
+    }
+}
+
+// The sizes of the cells must be positive.
 #[derive(Copy, Clone, Debug)]
 struct Size(usize);
 impl FromStr for Size {
@@ -152,6 +216,15 @@ enum OutputModeArg {
     PrettyColor,
     PrettyAscii,
     Raw,
+    Pzprv3,
+    Loopy,
+    LoopySave,
+    Json,
+    Path,
+    Svg,
+    Png,
+    Tikz,
+    TikzBare,
     None,
 }
 
@@ -164,15 +237,37 @@ impl FromStr for OutputModeArg {
             "pretty-color" => Ok(OutputModeArg::PrettyColor),
             "pretty-ascii" => Ok(OutputModeArg::PrettyAscii),
             "raw" => Ok(OutputModeArg::Raw),
+            "pzprv3" => Ok(OutputModeArg::Pzprv3),
+            "loopy" => Ok(OutputModeArg::Loopy),
+            "loopy-save" => Ok(OutputModeArg::LoopySave),
+            "json" => Ok(OutputModeArg::Json),
+            "path" => Ok(OutputModeArg::Path),
+            "svg" => Ok(OutputModeArg::Svg),
+            "png" => Ok(OutputModeArg::Png),
+            "tikz" => Ok(OutputModeArg::Tikz),
+            "tikz-bare" => Ok(OutputModeArg::TikzBare),
             "none" => Ok(OutputModeArg::None),
             _ => Err(()),
         }
     }
 }
 
+impl FromStr for ImageTheme {
+    type Err = ();
+
+    fn from_str(src: &str) -> Result<ImageTheme, ()> {
+        match src {
+            "color" => Ok(ImageTheme::Color),
+            "mono" => Ok(ImageTheme::Mono),
+            _ => Err(()),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 struct TestArgs {
     derive_all: bool,
+    strict: bool,
     input_files: Vec<String>,
 }
 
@@ -181,6 +276,10 @@ impl SetupParser for TestArgs {
         ap.set_description("Test the given problem(s)");
         let _ = ap.refer(&mut self.derive_all)
                   .add_option(&["--all"], StoreTrue, "derive all solutions (if any).");
+        let _ = ap.refer(&mut self.strict)
+                  .add_option(&["--strict"],
+                              StoreTrue,
+                              "reject unknown edges and text outside of the lattice.");
         let _ = ap.refer(&mut self.input_files)
                   .add_argument("input_files", List, "puzzle files to solve.");
     }
@@ -190,6 +289,7 @@ impl Default for TestArgs {
     fn default() -> TestArgs {
         TestArgs {
             derive_all: false,
+            strict: false,
             input_files: vec![],
         }
     }
@@ -199,6 +299,7 @@ impl Into<Config> for TestArgs {
     fn into(self) -> Config {
         Config::Test(TestConfig {
             derive_all: self.derive_all,
+            parse_mode: parse_mode(self.strict),
             input_files: self.input_files,
         })
     }
@@ -208,6 +309,7 @@ impl Into<Config> for TestArgs {
 struct BenchArgs {
     derive_all: bool,
     only_hardest: Option<usize>,
+    strict: bool,
     input_files: Vec<String>,
 }
 
@@ -221,6 +323,10 @@ impl SetupParser for BenchArgs {
                               StoreOption,
                               "measure only hardest n problems.")
                   .metavar("n");
+        let _ = ap.refer(&mut self.strict)
+                  .add_option(&["--strict"],
+                              StoreTrue,
+                              "reject unknown edges and text outside of the lattice.");
         let _ = ap.refer(&mut self.input_files)
                   .add_argument("input_files", List, "puzzle files to solve.");
     }
@@ -231,6 +337,7 @@ impl Default for BenchArgs {
         BenchArgs {
             derive_all: false,
             only_hardest: None,
+            strict: false,
             input_files: vec![],
         }
     }
@@ -241,28 +348,97 @@ impl Into<Config> for BenchArgs {
         Config::Bench(BenchConfig {
             derive_all: self.derive_all,
             only_hardest: self.only_hardest,
+            parse_mode: parse_mode(self.strict),
             input_files: self.input_files,
         })
     }
 }
 
+#[derive(Clone, Debug)]
+struct ImportArgs {
+    input_dir: String,
+    output_dir: String,
+}
+
+impl SetupParser for ImportArgs {
+    fn setup_parser<'parser>(&'parser mut self, ap: &mut ArgumentParser<'parser>) {
+        ap.set_description("Import the saved janko.at pages in the directory");
+        let _ = ap.refer(&mut self.input_dir)
+                  .required()
+                  .add_argument("input_dir", Store, "directory of the saved pages.");
+        let _ = ap.refer(&mut self.output_dir)
+                  .required()
+                  .add_argument("output_dir",
+                                Store,
+                                "directory to write the puzzles and the solutions.");
+    }
+}
+
+impl Default for ImportArgs {
+    fn default() -> ImportArgs {
+        ImportArgs {
+            input_dir: String::new(),
+            output_dir: String::new(),
+        }
+    }
+}
+
+impl Into<Config> for ImportArgs {
+    fn into(self) -> Config {
+        Config::Import(ImportConfig {
+            input_dir: self.input_dir,
+            output_dir: self.output_dir,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct DedupArgs {
+    inputs: Vec<String>,
+}
+
+impl SetupParser for DedupArgs {
+    fn setup_parser<'parser>(&'parser mut self, ap: &mut ArgumentParser<'parser>) {
+        ap.set_description("Find the puzzles which are the same up to rotation and reflection");
+        let _ = ap.refer(&mut self.inputs)
+                  .required()
+                  .add_argument("inputs", List, "puzzle files or directories to scan.");
+    }
+}
+
+impl Default for DedupArgs {
+    fn default() -> DedupArgs {
+        DedupArgs { inputs: vec![] }
+    }
+}
+
+impl Into<Config> for DedupArgs {
+    fn into(self) -> Config {
+        Config::Dedup(DedupConfig { inputs: self.inputs })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum Config {
     Solve(SolveConfig),
     Test(TestConfig),
     Bench(BenchConfig),
+    Import(ImportConfig),
+    Dedup(DedupConfig),
 }
 
 #[derive(Clone, Debug)]
 pub struct SolveConfig {
     pub derive_all: bool,
     pub output_mode: OutputMode,
+    pub parse_mode: ParseMode,
     pub input_files: Vec<String>,
 }
 
 #[derive(Clone, Debug)]
 pub struct TestConfig {
     pub derive_all: bool,
+    pub parse_mode: ParseMode,
     pub input_files: Vec<String>,
 }
 
@@ -270,13 +446,33 @@ pub struct TestConfig {
 pub struct BenchConfig {
     pub derive_all: bool,
     pub only_hardest: Option<usize>,
+    pub parse_mode: ParseMode,
     pub input_files: Vec<String>,
 }
 
+#[derive(Clone, Debug)]
+pub struct ImportConfig {
+    pub input_dir: String,
+    pub output_dir: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct DedupConfig {
+    pub inputs: Vec<String>,
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum OutputMode {
     Pretty(PpConfig),
     Raw,
+    Pzprv3,
+    Loopy,
+    LoopySave,
+    Json,
+    Path,
+    Svg(ImageConfig),
+    Png(ImageConfig),
+    Tikz(TikzMode),
     None,
 }
 
@@ -296,6 +492,8 @@ impl Config {
             CommandType::Solve => Self::parse_subcommand::<SolveArgs>(args),
             CommandType::Test => Self::parse_subcommand::<TestArgs>(args),
             CommandType::Bench => Self::parse_subcommand::<BenchArgs>(args),
+            CommandType::Import => Self::parse_subcommand::<ImportArgs>(args),
+            CommandType::Dedup => Self::parse_subcommand::<DedupArgs>(args),
         }
     }
 
@@ -313,3 +511,16 @@ impl Config {
         sub_args.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Size;
+
+    #[test]
+    fn size() {
+        assert_eq!(32, "32".parse::<Size>().unwrap().0);
+        assert!("0".parse::<Size>().is_err());
+        assert!("-1".parse::<Size>().is_err());
+        assert!("".parse::<Size>().is_err());
+    }
+}
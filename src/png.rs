@@ -0,0 +1,521 @@
+// Copyright (c) 2016 srither developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::cmp;
+use srither_core::puzzle::{Edge, Puzzle, Side};
+use srither_core::geom::{Geom, Point};
+
+use image::{self, Config, Metrics, Rgb};
+
+// 5x7 glyphs of the hints and the markers, one row per byte.
+const GLYPH_WIDTH: i32 = 5;
+const GLYPH_HEIGHT: i32 = 7;
+
+fn glyph(c: char) -> Option<[u8; 7]> {
+    let glyph = match c {
+        '0' => [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+        '1' => [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+        '2' => [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+        '3' => [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+        '4' => [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+        'S' => [0x0f, 0x10, 0x10, 0x0e, 0x01, 0x01, 0x1e],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a],
+        '?' => [0x0e, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+        _ => return None,
+    };
+    Some(glyph)
+}
+
+struct Canvas {
+    width: i32,
+    height: i32,
+    data: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: i32, height: i32, color: Rgb) -> Canvas {
+        let mut data = Vec::with_capacity((width * height * 3) as usize);
+        for _ in 0..(width * height) {
+            data.extend(&[color.0, color.1, color.2]);
+        }
+        Canvas {
+            width: width,
+            height: height,
+            data: data,
+        }
+    }
+
+    fn fill_rect(&mut self, x: i32, y: i32, w: i32, h: i32, color: Rgb) {
+        let (x0, y0) = (cmp::max(x, 0), cmp::max(y, 0));
+        let (x1, y1) = (cmp::min(x + w, self.width), cmp::min(y + h, self.height));
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let i = ((y * self.width + x) * 3) as usize;
+                self.data[i] = color.0;
+                self.data[i + 1] = color.1;
+                self.data[i + 2] = color.2;
+            }
+        }
+    }
+
+    fn fill_circle(&mut self, cx: i32, cy: i32, r: i32, color: Rgb) {
+        for dy in -r..(r + 1) {
+            for dx in -r..(r + 1) {
+                if dx * dx + dy * dy <= r * r {
+                    self.fill_rect(cx + dx, cy + dy, 1, 1, color);
+                }
+            }
+        }
+    }
+
+    fn draw_cross(&mut self, cx: i32, cy: i32, r: i32, width: i32, color: Rgb) {
+        let h = width / 2;
+        for t in -r..(r + 1) {
+            self.fill_rect(cx + t - h, cy + t - h, width, width, color);
+            self.fill_rect(cx + t - h, cy - t - h, width, width, color);
+        }
+    }
+
+    // Draws the text centered at the point, with a space of `scale` pixels
+    // between the glyphs.
+    fn draw_text(&mut self, cx: i32, cy: i32, text: &str, scale: i32, color: Rgb) {
+        let len = text.chars().count() as i32;
+        let x0 = cx - ((GLYPH_WIDTH + 1) * len - 1) * scale / 2;
+        let y0 = cy - GLYPH_HEIGHT * scale / 2;
+        for (i, c) in text.chars().enumerate() {
+            let glyph = match glyph(c) {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+            let x0 = x0 + (GLYPH_WIDTH + 1) * scale * i as i32;
+            for (row, bits) in glyph.iter().enumerate() {
+                for col in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                        self.fill_rect(x0 + col * scale,
+                                       y0 + row as i32 * scale,
+                                       scale,
+                                       scale,
+                                       color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn render(conf: &Config, puzzle: &Puzzle) -> Vec<u8> {
+    let palette = image::theme_to_palette(conf.theme);
+    let m = Metrics::new(conf);
+    let size = m.cell;
+    let mut canvas = Canvas::new(m.width(puzzle), m.height(puzzle), palette.background);
+
+    if conf.shade_inside {
+        for p in puzzle.points().filter(|&p| puzzle.side(p) == Some(Side::In)) {
+            let (x, y) = m.pos(p);
+            canvas.fill_rect(x, y, size, size, palette.inside);
+        }
+    }
+
+    let scale = cmp::max(size * 3 / 5 / GLYPH_HEIGHT, 1);
+    for p in puzzle.points() {
+        let text = match image::cell_text(puzzle, p) {
+            Some(text) => text,
+            None => continue,
+        };
+        // The sets of the numbers are written smaller to fit in the cells.
+        let len = text.len() as i32;
+        let scale = cmp::max(cmp::min(scale, size * 4 / 5 / ((GLYPH_WIDTH + 1) * len - 1)), 1);
+        let (x, y) = m.pos(p);
+        canvas.draw_text(x + size / 2, y + size / 2, &text, scale, palette.hint);
+    }
+
+    let lw = m.line_width;
+    let cw = cmp::max(lw / 2, 1);
+    for r in 0..(puzzle.row() + 1) {
+        for c in 0..puzzle.column() {
+            if !puzzle.is_drawn_edge_h(Point(r, c)) {
+                continue;
+            }
+            let (x, y) = m.pos(Point(r, c));
+            match puzzle.edge_h(Point(r, c)) {
+                Some(Edge::Line) => {
+                    canvas.fill_rect(x - lw / 2, y - lw / 2, size + lw, lw, palette.line)
+                }
+                Some(Edge::Cross) => canvas.draw_cross(x + size / 2, y, m.cross, cw, palette.cross),
+                None => {}
+            }
+        }
+    }
+    for r in 0..puzzle.row() {
+        for c in 0..(puzzle.column() + 1) {
+            if !puzzle.is_drawn_edge_v(Point(r, c)) {
+                continue;
+            }
+            let (x, y) = m.pos(Point(r, c));
+            match puzzle.edge_v(Point(r, c)) {
+                Some(Edge::Line) => {
+                    canvas.fill_rect(x - lw / 2, y - lw / 2, lw, size + lw, palette.line)
+                }
+                Some(Edge::Cross) => canvas.draw_cross(x, y + size / 2, m.cross, cw, palette.cross),
+                None => {}
+            }
+        }
+    }
+
+    // The hints of the vertices are drawn in circles over the lines.
+    let vh = m.vertex_hint;
+    let vh_scale = cmp::max(vh * 3 / 2 / GLYPH_HEIGHT, 1);
+    for r in 0..(puzzle.row() + 1) {
+        for c in 0..(puzzle.column() + 1) {
+            let p = Point(r, c);
+            if !puzzle.is_drawn_vertex(p) {
+                continue;
+            }
+            let (x, y) = m.pos(p);
+            match puzzle.vertex_hint(p) {
+                Some(n) => {
+                    canvas.fill_circle(x, y, vh, palette.dot);
+                    canvas.fill_circle(x, y, vh - cmp::max(lw / 4, 1), palette.background);
+                    canvas.draw_text(x, y, &n.to_string(), vh_scale, palette.hint);
+                }
+                None => canvas.fill_circle(x, y, m.dot, palette.dot),
+            }
+        }
+    }
+
+    encode(&canvas)
+}
+
+fn encode(canvas: &Canvas) -> Vec<u8> {
+    let stride = (canvas.width * 3) as usize;
+    let mut raw = Vec::with_capacity((stride + 1) * canvas.height as usize);
+    for row in canvas.data.chunks(stride) {
+        raw.push(0); // filter type: none
+        raw.extend(row);
+    }
+
+    let mut ihdr = vec![];
+    push_u32(&mut ihdr, canvas.width as u32);
+    push_u32(&mut ihdr, canvas.height as u32);
+    ihdr.extend(&[8, 2, 0, 0, 0]); // 8-bit RGB, no interlace
+
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+    push_chunk(&mut png, b"IHDR", &ihdr);
+    push_chunk(&mut png, b"IDAT", &zlib::compress(&raw, stride + 1));
+    push_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn push_u32(buf: &mut Vec<u8>, n: u32) {
+    buf.extend(&[(n >> 24) as u8, (n >> 16) as u8, (n >> 8) as u8, n as u8]);
+}
+
+fn push_chunk(buf: &mut Vec<u8>, ty: &[u8; 4], data: &[u8]) {
+    push_u32(buf, data.len() as u32);
+    let start = buf.len();
+    buf.extend(ty);
+    buf.extend(data);
+    let crc = crc32(&buf[start..]);
+    push_u32(buf, crc);
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xedb88320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+// A minimal zlib encoder using the fixed Huffman codes of deflate.
+//
+// The images consist of flat colors, so only the repetitions of the previous
+// pixel and of the previous scanline are searched.
+mod zlib {
+    use std::cmp;
+
+    const MAX_MATCH: usize = 258;
+    const MAX_DISTANCE: usize = 32768;
+
+    const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35,
+                                    43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+    const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4,
+                                    4, 4, 4, 5, 5, 5, 5, 0];
+    const DIST_BASE: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257,
+                                  385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193,
+                                  12289, 16385, 24577];
+    const DIST_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9,
+                                  9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+    struct BitWriter {
+        buf: Vec<u8>,
+        acc: u32,
+        len: u32,
+    }
+
+    impl BitWriter {
+        fn new() -> BitWriter {
+            BitWriter {
+                buf: vec![],
+                acc: 0,
+                len: 0,
+            }
+        }
+
+        // Writes the bits, least significant bit first.
+        fn bits(&mut self, value: u32, len: u32) {
+            self.acc |= value << self.len;
+            self.len += len;
+            while self.len >= 8 {
+                self.buf.push(self.acc as u8);
+                self.acc >>= 8;
+                self.len -= 8;
+            }
+        }
+
+        // Writes the Huffman code, most significant bit first.
+        fn code(&mut self, code: u32, len: u32) {
+            let mut rev = 0;
+            for i in 0..len {
+                rev |= ((code >> i) & 1) << (len - 1 - i);
+            }
+            self.bits(rev, len);
+        }
+
+        fn finish(mut self) -> Vec<u8> {
+            if self.len > 0 {
+                self.buf.push(self.acc as u8);
+            }
+            self.buf
+        }
+    }
+
+    fn literal(w: &mut BitWriter, sym: u32) {
+        match sym {
+            0...143 => w.code(0x30 + sym, 8),
+            144...255 => w.code(0x190 + sym - 144, 9),
+            256...279 => w.code(sym - 256, 7),
+            _ => w.code(0xc0 + sym - 280, 8),
+        }
+    }
+
+    fn copy(w: &mut BitWriter, len: usize, dist: usize) {
+        let i = LENGTH_BASE.iter().rposition(|&b| b as usize <= len).unwrap();
+        literal(w, 257 + i as u32);
+        w.bits((len - LENGTH_BASE[i] as usize) as u32, LENGTH_EXTRA[i] as u32);
+
+        let i = DIST_BASE.iter().rposition(|&b| b as usize <= dist).unwrap();
+        w.code(i as u32, 5);
+        w.bits((dist - DIST_BASE[i] as usize) as u32, DIST_EXTRA[i] as u32);
+    }
+
+    fn match_len(data: &[u8], pos: usize, dist: usize) -> usize {
+        if dist > pos || dist > MAX_DISTANCE {
+            return 0;
+        }
+        let max = cmp::min(MAX_MATCH, data.len() - pos);
+        (0..max).take_while(|&i| data[pos + i] == data[pos + i - dist]).count()
+    }
+
+    pub fn compress(data: &[u8], row_len: usize) -> Vec<u8> {
+        let mut w = BitWriter::new();
+        w.bits(0x78, 8);
+        w.bits(0x01, 8);
+        w.bits(1, 1); // final block
+        w.bits(1, 2); // fixed Huffman codes
+
+        let mut pos = 0;
+        while pos < data.len() {
+            let (len, dist) = cmp::max((match_len(data, pos, 3), 3),
+                                       (match_len(data, pos, row_len), row_len));
+            if len >= 3 {
+                copy(&mut w, len, dist);
+                pos += len;
+            } else {
+                literal(&mut w, data[pos] as u32);
+                pos += 1;
+            }
+        }
+        literal(&mut w, 256);
+
+        let mut buf = w.finish();
+        let adler = adler32(data);
+        buf.extend(&[(adler >> 24) as u8, (adler >> 16) as u8, (adler >> 8) as u8, adler as u8]);
+        buf
+    }
+
+    pub fn adler32(data: &[u8]) -> u32 {
+        let (mut a, mut b) = (1u32, 0u32);
+        for &x in data {
+            a = (a + x as u32) % 65521;
+            b = (b + a) % 65521;
+        }
+        (b << 16) | a
+    }
+
+    #[cfg(test)]
+    pub mod tests {
+        use super::{DIST_BASE, DIST_EXTRA, LENGTH_BASE, LENGTH_EXTRA};
+
+        struct BitReader<'a> {
+            data: &'a [u8],
+            pos: usize,
+        }
+
+        impl<'a> BitReader<'a> {
+            // Reads the bits, least significant bit first.
+            fn bits(&mut self, len: u32) -> u32 {
+                let mut value = 0;
+                for i in 0..len {
+                    let bit = (self.data[self.pos / 8] >> (self.pos % 8)) & 1;
+                    value |= (bit as u32) << i;
+                    self.pos += 1;
+                }
+                value
+            }
+
+            // Reads the Huffman code, most significant bit first.
+            fn code(&mut self, len: u32) -> u32 {
+                let mut code = 0;
+                for _ in 0..len {
+                    code = (code << 1) | self.bits(1);
+                }
+                code
+            }
+
+            fn symbol(&mut self) -> u32 {
+                let code = self.code(7);
+                if code < 0x18 {
+                    return 256 + code;
+                }
+                let code = (code << 1) | self.bits(1);
+                match code {
+                    0x30...0xbf => code - 0x30,
+                    0xc0...0xc7 => 280 + code - 0xc0,
+                    _ => 144 + ((code << 1) | self.bits(1)) - 0x190,
+                }
+            }
+        }
+
+        // Decodes the zlib stream of a single block with the fixed Huffman
+        // codes, which is the only kind of the blocks `compress` writes.
+        pub fn decompress(data: &[u8]) -> Vec<u8> {
+            assert_eq!(&[0x78, 0x01], &data[..2]);
+            let mut r = BitReader {
+                data: &data[2..(data.len() - 4)],
+                pos: 0,
+            };
+            assert_eq!((1, 1), (r.bits(1), r.bits(2)));
+
+            let mut out = vec![];
+            loop {
+                let sym = r.symbol();
+                if sym < 256 {
+                    out.push(sym as u8);
+                    continue;
+                }
+                if sym == 256 {
+                    break;
+                }
+                let i = (sym - 257) as usize;
+                let len = LENGTH_BASE[i] as usize + r.bits(LENGTH_EXTRA[i] as u32) as usize;
+                let i = r.code(5) as usize;
+                let dist = DIST_BASE[i] as usize + r.bits(DIST_EXTRA[i] as u32) as usize;
+                for _ in 0..len {
+                    let x = out[out.len() - dist];
+                    out.push(x);
+                }
+            }
+
+            let adler = data[(data.len() - 4)..]
+                            .iter()
+                            .fold(0, |acc, &x| (acc << 8) | x as u32);
+            assert_eq!(super::adler32(&out), adler);
+            out
+        }
+
+        #[test]
+        fn adler32() {
+            assert_eq!(1, super::adler32(b""));
+            assert_eq!(0x11e60398, super::adler32(b"Wikipedia"));
+        }
+
+        #[test]
+        fn compress() {
+            let mut data = vec![];
+            for i in 0..1000 {
+                data.push((i % 7) as u8);
+                data.push((i * i % 251) as u8);
+                data.extend(&[0xff; 3]);
+            }
+            for &row_len in &[1, 5, 40] {
+                assert_eq!(data, decompress(&super::compress(&data, row_len)));
+            }
+            assert_eq!(Vec::<u8>::new(), decompress(&super::compress(&[], 3)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgb;
+    use super::{Canvas, crc32, encode};
+    use super::zlib::tests::decompress;
+
+    fn read_u32(buf: &[u8]) -> u32 {
+        buf[..4].iter().fold(0, |acc, &x| (acc << 8) | x as u32)
+    }
+
+    #[test]
+    fn crc() {
+        assert_eq!(0, crc32(b""));
+        assert_eq!(0xae426082, crc32(b"IEND"));
+        assert_eq!(0xcbf43926, crc32(b"123456789"));
+    }
+
+    #[test]
+    fn decode() {
+        let mut canvas = Canvas::new(7, 5, Rgb(0xff, 0xff, 0xff));
+        canvas.fill_rect(1, 1, 3, 2, Rgb(0x20, 0x40, 0xc0));
+        canvas.fill_circle(5, 3, 1, Rgb(0x00, 0x00, 0x00));
+        let png = encode(&canvas);
+
+        assert_eq!(&[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a], &png[..8]);
+        let mut chunks = vec![];
+        let mut pos = 8;
+        while pos < png.len() {
+            let len = read_u32(&png[pos..]) as usize;
+            let body = &png[(pos + 4)..(pos + 8 + len)];
+            assert_eq!(crc32(body), read_u32(&png[(pos + 8 + len)..]));
+            chunks.push((&body[..4], &body[4..]));
+            pos += len + 12;
+        }
+        assert_eq!(pos, png.len());
+
+        let types = chunks.iter().map(|c| c.0).collect::<Vec<_>>();
+        assert_eq!(vec![&b"IHDR"[..], &b"IDAT"[..], &b"IEND"[..]], types);
+        let ihdr = chunks[0].1;
+        assert_eq!((7, 5), (read_u32(ihdr), read_u32(&ihdr[4..])));
+        assert_eq!(&[8, 2, 0, 0, 0], &ihdr[8..]);
+
+        let raw = decompress(chunks[1].1);
+        let mut expected = vec![];
+        for row in canvas.data.chunks(7 * 3) {
+            expected.push(0);
+            expected.extend(row);
+        }
+        assert_eq!(expected, raw);
+    }
+}
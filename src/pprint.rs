@@ -6,12 +6,12 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::{io, iter};
+use std::{cmp, io, iter};
 use std::io::Stdout;
 use std::io::prelude::*;
 use term::{self, StdoutTerminal, Terminal, color};
 use term::color::Color;
-use srither_core::puzzle::{Edge, Puzzle, Side};
+use srither_core::puzzle::{Edge, HintSet, Marker, Puzzle, Side};
 use srither_core::geom::{Geom, Move, Point};
 
 use error::AppResult;
@@ -146,6 +146,12 @@ impl Table {
         where P: Printer
     {
         let row = puzzle.row();
+        if let Some(length) = puzzle.loop_length() {
+            try!(printer.write_plain(&format!("length: {}\n", length)));
+        }
+        if let Some(area) = puzzle.area() {
+            try!(printer.write_plain(&format!("area: {}\n", area)));
+        }
         try!(self.label_row.pprint(printer, puzzle));
         for y in 0..row {
             try!(self.edge_row.pprint(printer, puzzle, y));
@@ -260,6 +266,15 @@ impl Corner {
     fn pprint<P>(&self, printer: &mut P, puzzle: &Puzzle, p: Point) -> AppResult<()>
         where P: Printer
     {
+        if let Some(n) = puzzle.vertex_hint(p) {
+            try!(printer.write_plain(&n.to_string()));
+            return Ok(());
+        }
+        if !puzzle.is_drawn_vertex(p) {
+            try!(printer.write_plain(" "));
+            return Ok(());
+        }
+
         let l = p + Move::LEFT;
         let u = p + Move::UP;
         let eh_p = puzzle.edge_h(p);
@@ -310,6 +325,11 @@ impl EdgeH {
     fn pprint<P>(&self, printer: &mut P, puzzle: &Puzzle, p: Point) -> AppResult<()>
         where P: Printer
     {
+        if !puzzle.is_drawn_edge_h(p) {
+            try!(printer.write_plain(&self.str_cross));
+            return Ok(());
+        }
+
         let (s, side) = match puzzle.edge_h(p) {
             Some(Edge::Cross) => (&self.str_cross, puzzle.side(p)),
             Some(Edge::Line) => (&self.str_line, None),
@@ -357,6 +377,11 @@ impl EdgeV {
     fn pprint<P>(&self, printer: &mut P, puzzle: &Puzzle, p: Point) -> AppResult<()>
         where P: Printer
     {
+        if !puzzle.is_drawn_edge_v(p) {
+            try!(printer.write_plain(" "));
+            return Ok(());
+        }
+
         let (s, side) = match puzzle.edge_v(p) {
             Some(Edge::Cross) => (" ", puzzle.side(p)),
             Some(Edge::Line) => ("|", None),
@@ -369,7 +394,11 @@ impl EdgeV {
 
 struct Cell {
     nums: [String; 5],
+    sheep: String,
+    wolf: String,
+    mask: String,
     space: String,
+    width: usize,
 }
 
 impl Cell {
@@ -380,16 +409,39 @@ impl Cell {
                    format!("{:^1$}", 2, conf.cell_width),
                    format!("{:^1$}", 3, conf.cell_width),
                    format!("{:^1$}", 4, conf.cell_width)],
+            sheep: format!("{:^1$}", "s", conf.cell_width),
+            wolf: format!("{:^1$}", "w", conf.cell_width),
+            mask: format!("{:^1$}", "#", conf.cell_width),
             space: format!("{:^1$}", "", conf.cell_width),
+            width: conf.cell_width,
         }
     }
 
+    fn hint_set(&self, set: HintSet) -> String {
+        format!("{:^1$}", set.to_string(), self.width)
+    }
+
     fn pprint<P>(&self, printer: &mut P, puzzle: &Puzzle, p: Point, num_line: bool) -> AppResult<()>
         where P: Printer
     {
+        if puzzle.is_masked(p) {
+            let s = if num_line { &self.mask } else { &self.space };
+            try!(printer.write_plain(s));
+            return Ok(());
+        }
+
         let side = puzzle.side(p);
-        match puzzle.hint(p) {
-            Some(x) if num_line => try!(printer.write_pretty(side, &self.nums[x as usize])),
+        match (puzzle.hint(p), puzzle.hint_set(p), puzzle.marker(p)) {
+            (_, _, Some(Marker::Sheep)) if num_line => {
+                try!(printer.write_pretty(side, &self.sheep))
+            }
+            (_, _, Some(Marker::Wolf)) if num_line => try!(printer.write_pretty(side, &self.wolf)),
+            (_, Some(set), None) if num_line => {
+                try!(printer.write_pretty(side, &self.hint_set(set)))
+            }
+            (Some(x), None, None) if num_line => {
+                try!(printer.write_pretty(side, &self.nums[x as usize]))
+            }
             _ => try!(printer.write_pretty(side, &self.space)),
         }
         Ok(())
@@ -399,7 +451,16 @@ impl Cell {
 pub fn print(conf: &Config, puzzle: &Puzzle) -> AppResult<()> {
     let is_color = conf.mode == Mode::Color;
 
-    let table = Table::new(conf);
+    // The cells are widened to the widest set of the numbers, like `Display`
+    // of the puzzle, so that the board can be read back.
+    let widest = puzzle.points()
+                       .filter_map(|p| puzzle.hint_set(p))
+                       .map(|set| set.to_string().len())
+                       .max()
+                       .unwrap_or(0);
+    let conf = Config { cell_width: cmp::max(conf.cell_width, widest), ..*conf };
+
+    let table = Table::new(&conf);
     match StdoutPrinter::new(is_color) {
         StdoutPrinter::Pretty(mut p) => table.pprint(&mut p, puzzle),
         StdoutPrinter::Raw(mut p) => table.pprint(&mut p, puzzle),
@@ -6,48 +6,163 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::io;
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::prelude::*;
+use std::io::{self, Read, Write};
 
-use srither_core::puzzle::Puzzle;
-use srither_solver::{Solutions, self as solver};
+use serde_json;
 
-use error::AppResult;
+use srither_core::graph::Board;
+use srither_core::loopy;
+use srither_core::puzzle::{Edge, Puzzle};
+use srither_core::pzpr;
+use srither_core::tikz::{Mode as TikzMode, Tikz};
+use srither_solver::{BoardSolutions, Solutions, self as solver};
+
+use error::{AppError, AppResult};
+use image::{self, Config as ImageConfig};
+use input::{self, Problem};
 use parse_arg::{OutputMode, SolveConfig};
+use png;
 use pprint;
+use svg;
 
 pub fn run(config: SolveConfig) -> AppResult<()> {
     if config.input_files.is_empty() {
-        try!(solve(&config, &mut io::stdin()));
+        let mut buf = String::new();
+        let _ = try!(io::stdin().read_to_string(&mut buf));
+        if let Some(board) = try!(input::parse_board("<stdin>", &buf)) {
+            return solve_board(&config, &board);
+        }
+        let problems = try!(input::parse_problems(&mut buf.as_bytes(), config.parse_mode));
+        try!(solve_all(&config, &problems));
     } else {
         for file in &config.input_files {
-            let mut f = try!(File::open(file));
-            try!(solve(&config, &mut f));
+            if let Some(board) = try!(input::read_board(file)) {
+                try!(solve_board(&config, &board));
+                continue;
+            }
+            let problems = try!(input::read_problems(file, config.parse_mode));
+            try!(solve_all(&config, &problems));
+        }
+    }
+
+    Ok(())
+}
+
+fn solve_all(config: &SolveConfig, problems: &[Problem]) -> AppResult<()> {
+    for problem in problems {
+        try!(solve(config, problem));
+    }
+
+    Ok(())
+}
+
+// Images are written to the files named after the input, such as
+// `puzzle.png`, or `puzzle.1.png` for each solution derived with `--all`.
+fn solve(config: &SolveConfig, problem: &Problem) -> AppResult<()> {
+    // The bare picture shows the puzzle itself, which may not be solvable yet.
+    if let OutputMode::Tikz(TikzMode::Bare) = config.output_mode {
+        label(config, problem, None);
+        print!("{}", Tikz::new(&problem.puzzle, TikzMode::Bare));
+        return Ok(());
+    }
+
+    if config.derive_all {
+        for (i, solution) in try!(Solutions::new(&problem.puzzle)).enumerate() {
+            try!(output(&config, problem, Some(i + 1), solution));
         }
+    } else {
+        let solution = try!(solver::solve(&problem.puzzle));
+        try!(output(&config, problem, None, solution));
     }
 
     Ok(())
 }
 
-fn solve<T: Read>(config: &SolveConfig, input: &mut T) -> AppResult<()> {
-    let mut buf = String::new();
-    let _ = try!(input.read_to_string(&mut buf));
-    let puzzle = try!(buf.parse::<Puzzle>());
+// The name of the result of the entry of a collection, such as
+// `puzzles.txt:001`, or `puzzles.txt:001.2` for the second solution derived
+// with `--all`.
+fn entry_name(problem: &Problem, index: Option<usize>) -> Option<String> {
+    problem.entry.as_ref().map(|_| {
+        let suffix = index.map_or(String::new(), |i| format!(".{}", i));
+        format!("{}{}", problem.name, suffix)
+    })
+}
+
+// The pretty outputs of the entries of a collection are headed by their names.
+// The raw outputs are headed by `[001]` instead, so that they make a collection,
+// and the JSON outputs are the objects with the names (see `output`). The names
+// of the other outputs are written to the standard error, so that the standard
+// output stays a pzprv3 file, a list of Loopy game IDs and so on. The images
+// are written to the files named after the entries.
+fn label(config: &SolveConfig, problem: &Problem, index: Option<usize>) {
+    let entry = match problem.entry {
+        Some(ref entry) => entry,
+        None => return,
+    };
+    let suffix = index.map_or(String::new(), |i| format!(".{}", i));
+    match config.output_mode {
+        OutputMode::Pretty(_) => println!("{}{}", problem.name, suffix),
+        OutputMode::Raw => println!("[{}{}]", entry, suffix),
+        OutputMode::Json | OutputMode::Svg(_) | OutputMode::Png(_) | OutputMode::None => {}
+        _ => {
+            let _ = writeln!(&mut io::stderr(), "{}{}", problem.name, suffix);
+        }
+    }
+}
+
+// The boards of the other tilings can only be printed as the positions of
+// the vertices on the loop.
+fn solve_board(config: &SolveConfig, board: &Board) -> AppResult<()> {
+    match config.output_mode {
+        OutputMode::Path | OutputMode::None => {}
+        _ => return Err(AppError::Unsupported("the boards are written by the path output only")),
+    }
 
     if config.derive_all {
-        for solution in try!(Solutions::new(&puzzle)) {
-            try!(output(&config, solution));
+        for solution in try!(BoardSolutions::new(board)) {
+            output_board(config, &solution);
         }
     } else {
-        let solution = try!(solver::solve(&puzzle));
-        try!(output(&config, solution));
+        let solution = try!(solver::solve_board(board));
+        output_board(config, &solution);
     }
 
     Ok(())
 }
 
-fn output(config: &SolveConfig, solution: Puzzle) -> AppResult<()> {
+fn output_board(config: &SolveConfig, solution: &Board) {
+    if let OutputMode::None = config.output_mode {
+        return;
+    }
+
+    let lines = (0..solution.num_edges())
+                    .map(|e| solution.edge(e) == Some(Edge::Line))
+                    .collect::<Vec<_>>();
+    let path = solution.loop_path(&lines).unwrap();
+    let path = path.iter()
+                   .map(|&v| {
+                       let (x, y) = solution.position(v);
+                       format!("{:.3},{:.3}", x, y)
+                   })
+                   .collect::<Vec<_>>();
+    println!("{}", path.join(" "));
+}
+
+fn output(config: &SolveConfig,
+          problem: &Problem,
+          index: Option<usize>,
+          solution: Puzzle)
+          -> AppResult<()> {
+    let file_stem = match index {
+        Some(i) => Some(format!("{}.{}", problem.file_stem, i)),
+        None if problem.entry.is_some() => Some(problem.file_stem.clone()),
+        None => None,
+    };
+    let file_stem = file_stem.as_ref().map(|s| &s[..]);
+
+    label(config, problem, index);
     match config.output_mode {
         OutputMode::Pretty(conf) => {
             try!(pprint::print(&conf, &solution));
@@ -55,8 +170,68 @@ fn output(config: &SolveConfig, solution: Puzzle) -> AppResult<()> {
         OutputMode::Raw => {
             print!("{}", solution.to_string());
         }
+        OutputMode::Pzprv3 => {
+            print!("{}", try!(pzpr::to_file(&solution)));
+        }
+        OutputMode::Loopy => {
+            println!("{}", try!(loopy::to_game_id(&solution)));
+        }
+        OutputMode::LoopySave => {
+            print!("{}", try!(loopy::to_save(&solution)));
+        }
+        OutputMode::Json => {
+            // The entries of a collection are the objects such as
+            // `{"name":"puzzles.txt:001","puzzle":{...}}`, one in each line.
+            match entry_name(problem, index) {
+                Some(name) => {
+                    let mut obj = BTreeMap::new();
+                    let _ = obj.insert("name", serde_json::to_value(&name));
+                    let _ = obj.insert("puzzle", serde_json::to_value(&solution));
+                    println!("{}", try!(serde_json::to_string(&obj)));
+                }
+                None => println!("{}", try!(serde_json::to_string(&solution))),
+            }
+        }
+        OutputMode::Path => {
+            let path = try!(solution.loop_path());
+            let path = path.iter().map(|p| format!("{},{}", p.0, p.1)).collect::<Vec<_>>();
+            println!("{}", path.join(" "));
+        }
+        OutputMode::Svg(conf) => {
+            try!(check_image_size(&conf, &solution));
+            try!(write_image(file_stem, "svg", svg::render(&conf, &solution).as_bytes()));
+        }
+        OutputMode::Png(conf) => {
+            try!(check_image_size(&conf, &solution));
+            try!(write_image(file_stem, "png", &png::render(&conf, &solution)));
+        }
+        OutputMode::Tikz(mode) => {
+            print!("{}", Tikz::new(&solution, mode));
+        }
         OutputMode::None => {}
     }
 
     Ok(())
 }
+
+fn check_image_size(conf: &ImageConfig, puzzle: &Puzzle) -> AppResult<()> {
+    if image::fits(conf, puzzle) {
+        Ok(())
+    } else {
+        Err(AppError::Unsupported("the cell size is too large for the board"))
+    }
+}
+
+fn write_image(file_stem: Option<&str>, ext: &str, data: &[u8]) -> AppResult<()> {
+    let path = match file_stem {
+        Some(stem) => format!("{}.{}", stem, ext),
+        None => {
+            try!(io::stdout().write_all(data));
+            return Ok(());
+        }
+    };
+
+    try!(try!(File::create(&path)).write_all(data));
+    println!("{}", path);
+    Ok(())
+}
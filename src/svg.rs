@@ -0,0 +1,156 @@
+// Copyright (c) 2016 srither developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::cmp;
+use std::fmt::{self, Write};
+use srither_core::puzzle::{Edge, Puzzle, Side};
+use srither_core::geom::{Geom, Point};
+
+use image::{self, Config, Metrics, Rgb};
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+pub fn render(conf: &Config, puzzle: &Puzzle) -> String {
+    let palette = image::theme_to_palette(conf.theme);
+    let m = Metrics::new(conf);
+    let size = m.cell;
+    let (width, height) = (m.width(puzzle), m.height(puzzle));
+
+    let mut svg = String::new();
+    let _ = writeln!(svg,
+                     r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+                     width,
+                     height);
+    let _ = writeln!(svg,
+                     r#"<rect width="{}" height="{}" fill="{}"/>"#,
+                     width,
+                     height,
+                     palette.background);
+
+    if conf.shade_inside {
+        for p in puzzle.points().filter(|&p| puzzle.side(p) == Some(Side::In)) {
+            let (x, y) = m.pos(p);
+            let _ = writeln!(svg,
+                             r#"<rect x="{}" y="{}" width="{2}" height="{2}" fill="{3}"/>"#,
+                             x,
+                             y,
+                             size,
+                             palette.inside);
+        }
+    }
+
+    let font_size = size * 3 / 5;
+    for p in puzzle.points() {
+        let text = match image::cell_text(puzzle, p) {
+            Some(text) => text,
+            None => continue,
+        };
+        // The sets of the numbers are written smaller to fit in the cells.
+        let (x, y) = m.pos(p);
+        let _ = writeln!(svg,
+                         r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" dominant-baseline="central" fill="{}">{}</text>"#,
+                         x + size / 2,
+                         y + size / 2,
+                         font_size * 3 / (text.len() as i32 + 2),
+                         palette.hint,
+                         text);
+    }
+
+    let cross = m.cross;
+    let edge = |svg: &mut String, edge: Option<Edge>, (x0, y0): (i32, i32), (x1, y1): (i32, i32)| {
+        match edge {
+            Some(Edge::Line) => {
+                let _ = writeln!(svg,
+                                 r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-linecap="round"/>"#,
+                                 x0,
+                                 y0,
+                                 x1,
+                                 y1,
+                                 palette.line,
+                                 m.line_width);
+            }
+            Some(Edge::Cross) => {
+                let (cx, cy) = ((x0 + x1) / 2, (y0 + y1) / 2);
+                let _ = writeln!(svg,
+                                 r#"<path d="M{} {}L{} {}M{} {}L{} {}" stroke="{}" stroke-width="{}"/>"#,
+                                 cx - cross,
+                                 cy - cross,
+                                 cx + cross,
+                                 cy + cross,
+                                 cx - cross,
+                                 cy + cross,
+                                 cx + cross,
+                                 cy - cross,
+                                 palette.cross,
+                                 cmp::max(m.line_width / 2, 1));
+            }
+            None => {}
+        }
+    };
+    for r in 0..(puzzle.row() + 1) {
+        for c in 0..puzzle.column() {
+            let p = Point(r, c);
+            if puzzle.is_drawn_edge_h(p) {
+                edge(&mut svg, puzzle.edge_h(p), m.pos(p), m.pos(Point(r, c + 1)));
+            }
+        }
+    }
+    for r in 0..puzzle.row() {
+        for c in 0..(puzzle.column() + 1) {
+            let p = Point(r, c);
+            if puzzle.is_drawn_edge_v(p) {
+                edge(&mut svg, puzzle.edge_v(p), m.pos(p), m.pos(Point(r + 1, c)));
+            }
+        }
+    }
+
+    // The hints of the vertices are drawn in circles over the lines.
+    for r in 0..(puzzle.row() + 1) {
+        for c in 0..(puzzle.column() + 1) {
+            let p = Point(r, c);
+            if !puzzle.is_drawn_vertex(p) {
+                continue;
+            }
+            let (x, y) = m.pos(p);
+            match puzzle.vertex_hint(p) {
+                Some(n) => {
+                    let _ = writeln!(svg,
+                                     r#"<circle cx="{}" cy="{}" r="{}" fill="{}" stroke="{}" stroke-width="{}"/>"#,
+                                     x,
+                                     y,
+                                     m.vertex_hint,
+                                     palette.background,
+                                     palette.dot,
+                                     cmp::max(m.line_width / 4, 1));
+                    let _ = writeln!(svg,
+                                     r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" dominant-baseline="central" fill="{}">{}</text>"#,
+                                     x,
+                                     y,
+                                     m.vertex_hint * 3 / 2,
+                                     palette.hint,
+                                     n);
+                }
+                None => {
+                    let _ = writeln!(svg,
+                                     r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                                     x,
+                                     y,
+                                     m.dot,
+                                     palette.dot);
+                }
+            }
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
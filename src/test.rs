@@ -6,51 +6,115 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::fs::File;
-use std::io::prelude::*;
 use rustc_test::{DynTestFn, DynTestName, ShouldPanic, TestDesc, TestDescAndFn, self as test};
 
+use srither_core::geom::{Geom, Point};
+use srither_core::graph::Board;
 use srither_core::puzzle::Puzzle;
-use srither_solver::{Solutions, self as solver};
+use srither_solver::{BoardSolutions, Solutions, self as solver};
 
 use error::AppResult;
+use input::{self, Problem};
 use parse_arg::TestConfig;
 
 pub fn run(config: TestConfig) -> AppResult<()> {
     let derive_all = config.derive_all;
-    let tests = config.input_files
-                      .into_iter()
-                      .map(|input| {
-                          TestDescAndFn {
-                              desc: TestDesc {
-                                  name: DynTestName(input.clone()),
-                                  ignore: false,
-                                  should_panic: ShouldPanic::No,
-                              },
-                              testfn: DynTestFn(Box::new(move || {
-                                  solve(&input, derive_all).unwrap()
-                              })),
-                          }
-                      })
-                      .collect();
+    let mut tests = vec![];
+    for input in config.input_files {
+        // An input which cannot be read is reported as a failed test.
+        match input::read_board(&input) {
+            Ok(Some(board)) => {
+                let name = input.clone();
+                tests.push(test_desc_and_fn(name, move || solve_board(&board, derive_all).unwrap()));
+                continue;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                let msg = e.to_string();
+                tests.push(test_desc_and_fn(input, move || panic!("{}", msg)));
+                continue;
+            }
+        }
+        let problems = match input::read_problems(&input, config.parse_mode) {
+            Ok(problems) => problems,
+            Err(e) => {
+                let msg = e.to_string();
+                tests.push(test_desc_and_fn(input, move || panic!("{}", msg)));
+                continue;
+            }
+        };
+        for problem in problems {
+            let name = problem.name.clone();
+            tests.push(test_desc_and_fn(name, move || solve(&problem, derive_all).unwrap()));
+        }
+    }
 
     test::test_main(&["".to_owned()], tests);
 
     Ok(())
 }
 
-fn solve(file: &str, derive_all: bool) -> AppResult<()> {
-    let mut buf = String::new();
-    let _ = try!(try!(File::open(file)).read_to_string(&mut buf));
-    let puzzle = try!(buf.parse::<Puzzle>());
+fn test_desc_and_fn<F>(name: String, f: F) -> TestDescAndFn
+    where F: FnOnce() + Send + 'static
+{
+    TestDescAndFn {
+        desc: TestDesc {
+            name: DynTestName(name),
+            ignore: false,
+            should_panic: ShouldPanic::No,
+        },
+        testfn: DynTestFn(Box::new(f)),
+    }
+}
+
+fn solve(problem: &Problem, derive_all: bool) -> AppResult<()> {
+    let expected = problem.solution.as_ref();
 
     if derive_all {
-        for solution in try!(Solutions::new(&puzzle)) {
+        for solution in try!(Solutions::new(&problem.puzzle)) {
+            check(&solution, expected);
+        }
+    } else {
+        check(&try!(solver::solve(&problem.puzzle)), expected);
+    }
+
+    Ok(())
+}
+
+fn solve_board(board: &Board, derive_all: bool) -> AppResult<()> {
+    if derive_all {
+        for solution in try!(BoardSolutions::new(board)) {
             let _ = test::black_box(solution);
         }
     } else {
-        let _ = test::black_box(try!(solver::solve(&puzzle)));
+        let _ = test::black_box(try!(solver::solve_board(board)));
     }
 
     Ok(())
 }
+
+fn check(solution: &Puzzle, expected: Option<&Puzzle>) {
+    let expected = match expected {
+        Some(expected) => expected,
+        None => {
+            let _ = test::black_box(solution);
+            return;
+        }
+    };
+
+    assert_eq!(expected.size(), solution.size());
+    let h_edges = (0..(solution.row() + 1))
+                      .flat_map(|r| (0..solution.column()).map(move |c| Point(r, c)));
+    for p in h_edges {
+        assert!(solution.edge_h(p) == expected.edge_h(p),
+                "solution differs from the expected solution at {:?}",
+                p);
+    }
+    let v_edges = (0..solution.row())
+                      .flat_map(|r| (0..(solution.column() + 1)).map(move |c| Point(r, c)));
+    for p in v_edges {
+        assert!(solution.edge_v(p) == expected.edge_v(p),
+                "solution differs from the expected solution at {:?}",
+                p);
+    }
+}
@@ -0,0 +1,386 @@
+// Copyright (c) 2016 srither-core developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Collection files holding several puzzles.
+//!
+//! Each entry of a collection starts with its name in brackets, followed by
+//! optional header lines, the puzzle and the optional expected solution.
+//! The puzzle and the solution are written in any format `Puzzle::from_str`
+//! accepts. Lines starting with `#` before the first entry are comments.
+//!
+//! ```text
+//! # Puzzles for the weekend.
+//! [001]
+//! title: First one
+//! author: gifnksm
+//! source: Nikoli
+//! difficulty: easy
+//! 3__
+//! _2_
+//! __3
+//! solution:
+//! +-+-+x+
+//! |3x | x
+//! +-+x+-+
+//! x |2x |
+//! +x+-+x+
+//! x x |3|
+//! +x+x+-+
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+use diagnostic::Span;
+use puzzle::{ParseMode, ParsePuzzleError, Puzzle};
+
+/// An error type which is returned from parsing a collection file.
+#[derive(Copy, Clone, Debug)]
+pub struct ParseCollectionError {
+    kind: CollectionErrorKind,
+    span: Option<Span>,
+}
+
+/// Collection parse result.
+pub type ParseCollectionResult<T> = Result<T, ParseCollectionError>;
+
+#[derive(Copy, Clone, Debug)]
+enum CollectionErrorKind {
+    NoEntry,
+    TextOutsideEntry,
+    NoPuzzle,
+    Puzzle(ParsePuzzleError),
+}
+
+impl From<ParsePuzzleError> for ParseCollectionError {
+    fn from(err: ParsePuzzleError) -> ParseCollectionError {
+        ParseCollectionError {
+            kind: CollectionErrorKind::Puzzle(err),
+            span: err.span(),
+        }
+    }
+}
+
+impl Error for ParseCollectionError {
+    fn description(&self) -> &str {
+        use self::CollectionErrorKind::*;
+        match self.kind {
+            NoEntry => "cannot found any entry in string",
+            TextOutsideEntry => "text found outside of entries",
+            NoPuzzle => "cannot found puzzle in entry",
+            Puzzle(ref e) => e.description(),
+        }
+    }
+    fn cause(&self) -> Option<&Error> {
+        use self::CollectionErrorKind::*;
+        match self.kind {
+            NoEntry | TextOutsideEntry | NoPuzzle => None,
+            Puzzle(ref e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for ParseCollectionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{} at {}", self.description(), span),
+            None => self.description().fmt(f),
+        }
+    }
+}
+
+impl ParseCollectionError {
+    fn no_entry() -> ParseCollectionError {
+        ParseCollectionError {
+            kind: CollectionErrorKind::NoEntry,
+            span: None,
+        }
+    }
+    fn text_outside_entry(span: Span) -> ParseCollectionError {
+        ParseCollectionError {
+            kind: CollectionErrorKind::TextOutsideEntry,
+            span: Some(span),
+        }
+    }
+    fn no_puzzle() -> ParseCollectionError {
+        ParseCollectionError {
+            kind: CollectionErrorKind::NoPuzzle,
+            span: None,
+        }
+    }
+    fn shift_lines(self, n: usize) -> ParseCollectionError {
+        ParseCollectionError { span: self.span.map(|s| s.shift_lines(n)), ..self }
+    }
+
+    /// Returns the position of the error in the collection file, if known.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+}
+
+/// An entry of a collection.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    /// The name of the entry.
+    pub name: String,
+    /// The title of the puzzle.
+    pub title: Option<String>,
+    /// The author of the puzzle.
+    pub author: Option<String>,
+    /// Where the puzzle comes from.
+    pub source: Option<String>,
+    /// The difficulty of the puzzle.
+    pub difficulty: Option<String>,
+    /// The puzzle.
+    pub puzzle: Puzzle,
+    /// The expected solution of the puzzle.
+    pub solution: Option<Puzzle>,
+}
+
+fn entry_name(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if line.starts_with('[') && line.ends_with(']') && line.len() >= 2 {
+        Some(line[1..line.len() - 1].trim())
+    } else {
+        None
+    }
+}
+
+fn is_comment(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty() || line.starts_with('#')
+}
+
+/// Returns true if the string looks like a collection file.
+pub fn is_collection(s: &str) -> bool {
+    s.lines().find(|l| !is_comment(l)).and_then(entry_name).is_some()
+}
+
+/// Parses a collection file into its entries.
+///
+/// Returns the first error if any entry cannot be parsed.
+pub fn parse(s: &str) -> ParseCollectionResult<Vec<Entry>> {
+    parse_all(s).map_err(|errors| errors[0])
+}
+
+/// Parses a collection file into its entries, collecting the errors of all
+/// the entries which cannot be parsed.
+///
+/// The returned vector of errors is never empty.
+pub fn parse_all(s: &str) -> Result<Vec<Entry>, Vec<ParseCollectionError>> {
+    parse_all_with_mode(s, ParseMode::Lenient)
+}
+
+/// Parses a collection file like `parse_all`, parsing the puzzles in the
+/// given mode.
+pub fn parse_all_with_mode(s: &str,
+                           mode: ParseMode)
+                           -> Result<Vec<Entry>, Vec<ParseCollectionError>> {
+    use self::ParseCollectionError as Error;
+
+    // The name of the entry, the line of the name and the following lines.
+    let mut blocks = vec![];
+    let mut errors = vec![];
+    for (i, line) in s.lines().enumerate() {
+        if let Some(name) = entry_name(line) {
+            blocks.push((name, i, vec![]));
+            continue;
+        }
+        match blocks.last_mut() {
+            Some(&mut (_, _, ref mut lines)) => lines.push(line),
+            None if is_comment(line) => {}
+            None => {
+                let span = Span::new(i, 0, line.chars().count());
+                errors.push(Error::text_outside_entry(span))
+            }
+        }
+    }
+    if blocks.is_empty() && errors.is_empty() {
+        return Err(vec![Error::no_entry()]);
+    }
+
+    let mut entries = vec![];
+    for (name, line, lines) in blocks {
+        match parse_entry(name, &lines, mode) {
+            Ok(entry) => entries.push(entry),
+            Err(e) if e.span.is_some() => errors.push(e.shift_lines(line + 1)),
+            Err(e) => {
+                // Points to the name of the entry.
+                let len = s.lines().nth(line).map_or(0, |l| l.chars().count());
+                errors.push(Error { span: Some(Span::new(line, 0, len)), ..e })
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(entries)
+    } else {
+        Err(errors)
+    }
+}
+
+// The spans of the errors are relative to the first line of `lines`.
+fn parse_entry(name: &str, lines: &[&str], mode: ParseMode) -> ParseCollectionResult<Entry> {
+    let (mut title, mut author, mut source, mut difficulty) = (None, None, None, None);
+    let (mut puzzle, mut puzzle_line) = (String::new(), 0);
+    let (mut solution, mut solution_line) = (None, 0);
+
+    let mut in_header = true;
+    for (i, line) in lines.iter().enumerate() {
+        if in_header {
+            let mut it = line.splitn(2, ':');
+            let key = it.next().unwrap_or("").trim();
+            match (key, it.next().map(|v| v.trim().to_owned())) {
+                ("title", Some(v)) => title = Some(v),
+                ("author", Some(v)) => author = Some(v),
+                ("source", Some(v)) => source = Some(v),
+                ("difficulty", Some(v)) => difficulty = Some(v),
+                _ if line.trim().is_empty() => {}
+                _ => {
+                    in_header = false;
+                    puzzle_line = i;
+                }
+            }
+            if in_header {
+                continue;
+            }
+        }
+
+        if line.trim() == "solution:" {
+            solution = Some(String::new());
+            solution_line = i + 1;
+            continue;
+        }
+        let buf = match solution {
+            Some(ref mut buf) => buf,
+            None => &mut puzzle,
+        };
+        buf.push_str(line);
+        buf.push('\n');
+    }
+
+    if puzzle.trim().is_empty() {
+        return Err(ParseCollectionError::no_puzzle());
+    }
+    let puzzle = try!(Puzzle::parse_with_mode(puzzle.trim_right(), mode)
+                          .map_err(|e| ParseCollectionError::from(e).shift_lines(puzzle_line)));
+    let solution = match solution {
+        Some(s) => {
+            Some(try!(Puzzle::parse_with_mode(s.trim_right(), mode)
+                          .map_err(|e| ParseCollectionError::from(e).shift_lines(solution_line))))
+        }
+        None => None,
+    };
+
+    Ok(Entry {
+        name: name.to_owned(),
+        title: title,
+        author: author,
+        source: source,
+        difficulty: difficulty,
+        puzzle: puzzle,
+        solution: solution,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+    use super::{ParseCollectionError, ParseCollectionResult};
+    use diagnostic::Span;
+    use geom::{Geom, Point, Size};
+    use puzzle::Edge;
+
+    fn check_error<T>(result: ParseCollectionResult<T>, error: ParseCollectionError) {
+        match result {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => assert_eq!(e.description(), error.description()),
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let input = "# comment
+
+[first]
+title: First one
+author: gifnksm
+difficulty: easy
+3_
+__
+solution:
++-+ +
+|3| x
++ + +
+x x x
++ + +
+
+[second]
+1__
+";
+        assert!(super::is_collection(input));
+        let entries = super::parse(input).unwrap();
+        assert_eq!(2, entries.len());
+
+        assert_eq!("first", entries[0].name);
+        assert_eq!(Some("First one".to_owned()), entries[0].title);
+        assert_eq!(Some("gifnksm".to_owned()), entries[0].author);
+        assert_eq!(None, entries[0].source);
+        assert_eq!(Some("easy".to_owned()), entries[0].difficulty);
+        assert_eq!(Size(2, 2), entries[0].puzzle.size());
+        assert_eq!(Some(3), entries[0].puzzle.hint(Point(0, 0)));
+        let solution = entries[0].solution.as_ref().unwrap();
+        assert_eq!(Some(Edge::Line), solution.edge_h(Point(0, 0)));
+        assert_eq!(Some(Edge::Cross), solution.edge_v(Point(1, 0)));
+
+        assert_eq!("second", entries[1].name);
+        assert_eq!(None, entries[1].title);
+        assert_eq!(Size(1, 3), entries[1].puzzle.size());
+        assert_eq!(None, entries[1].solution);
+
+        assert!(!super::is_collection("3_\n__\n"));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        check_error(super::parse("# comment\n"), ParseCollectionError::no_entry());
+        check_error(super::parse("3_\n[first]\n3_\n"),
+                    ParseCollectionError::text_outside_entry(Span::new(0, 0, 2)));
+        check_error(super::parse("[first]\ntitle: x\n[second]\n3\n"),
+                    ParseCollectionError::no_puzzle());
+        check_error(super::parse("[first]\nsolution:\n+-+\n"),
+                    ParseCollectionError::no_puzzle());
+    }
+
+    #[test]
+    fn parse_all_errors() {
+        let input = "[first]
+title: x
+
+3a
+[second]
+3_
+__
+[third]
+3_
+solution:
+
++-+ +
+|3|5 
++-+ +
+[fourth]
+";
+        let errors = super::parse_all(input).unwrap_err();
+        let spans = errors.iter().map(|e| e.span()).collect::<Vec<_>>();
+        assert_eq!(vec![Some(Span::new(3, 1, 1)),
+                        Some(Span::new(12, 3, 1)),
+                        Some(Span::new(14, 0, 8))],
+                   spans);
+        assert_eq!("cannot found puzzle in entry", errors[2].description());
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (c) 2016 srither-core developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Source positions of parse errors and their rendering.
+
+use std::{cmp, fmt};
+use std::fmt::Write;
+
+/// A range of characters in a line of the parsed string.
+///
+/// Both the line and the column are counted from zero.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Span {
+    /// The line of the range.
+    pub line: usize,
+    /// The column of the first character in the range.
+    pub column: usize,
+    /// The number of characters in the range.
+    pub len: usize,
+}
+
+impl Span {
+    /// Creates a new span.
+    pub fn new(line: usize, column: usize, len: usize) -> Span {
+        Span {
+            line: line,
+            column: column,
+            len: len,
+        }
+    }
+
+    /// Returns the span moved down by `n` lines.
+    pub fn shift_lines(self, n: usize) -> Span {
+        Span { line: self.line + n, ..self }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line + 1, self.column + 1)
+    }
+}
+
+/// Renders the error message with the line of the source it points to.
+///
+/// ```text
+/// error: invalid hint found in string
+///   --> puzzle.txt:2:3
+///   |
+/// 2 | 3_5_
+///   |   ^
+/// ```
+pub fn render(name: &str, source: &str, span: Span, message: &str) -> String {
+    let line = source.lines().nth(span.line).unwrap_or("");
+    let number = (span.line + 1).to_string();
+    let pad = number.chars().map(|_| ' ').collect::<String>();
+
+    let mut s = String::new();
+    let _ = writeln!(s, "error: {}", message);
+    let _ = writeln!(s, "{} --> {}:{}:{}", pad, name, span.line + 1, span.column + 1);
+    let _ = writeln!(s, "{} |", pad);
+    let _ = writeln!(s, "{} | {}", number, line);
+    let _ = writeln!(s,
+                     "{} | {:2$}{3}",
+                     pad,
+                     "",
+                     span.column,
+                     (0..cmp::max(span.len, 1)).map(|_| '^').collect::<String>());
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Span;
+
+    #[test]
+    fn render() {
+        let source = "3___\n_2a_\n";
+        let expected = "error: invalid hint found in string
+  --> input:2:3
+  |
+2 | _2a_
+  |   ^
+";
+        assert_eq!(expected,
+                   super::render("input",
+                                 source,
+                                 Span::new(1, 2, 1),
+                                 "invalid hint found in string"));
+    }
+}
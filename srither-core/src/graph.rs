@@ -0,0 +1,778 @@
+// Copyright (c) 2016 srither-core developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Boards of any planar tiling, held as graphs of faces, edges and vertices.
+//!
+//! A board is written as the name of its tiling followed by the rows of its
+//! hints, where `0`-`9` are hints and `_` or `-` are faces without hint.
+//!
+//! ```text
+//! hexagonal
+//! 3_2
+//! _1_
+//! ```
+//!
+//! * `square`: each row has one square per character.
+//! * `hexagonal`: pointy-top hexagons, where the odd rows are shifted right
+//!   by half a hexagon.
+//! * `triangular`: each row has triangles pointing up and down by turns,
+//!   starting with one pointing up in the even rows and down in the odd
+//!   rows.
+//! * `cairo`: Cairo pentagons, paired in squares of two characters. The
+//!   squares are split vertically (left and right) and horizontally (top and
+//!   bottom) by turns like a checkerboard, starting with a vertical split at
+//!   the top-left, so the rows have an even number of characters.
+//!
+//! The solver runs on boards, and a puzzle of the square grid is converted
+//! into a board by `Board::from_puzzle`.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use diagnostic::Span;
+use geom::{Geom, Point, Size};
+use puzzle::{Edge, Hint, HintSet, Marker, Puzzle, Side};
+
+/// A tiling of the board.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Tiling {
+    /// Squares.
+    Square,
+    /// Hexagons.
+    Hexagonal,
+    /// Triangles.
+    Triangular,
+    /// Cairo pentagons.
+    Cairo,
+}
+
+impl Tiling {
+    fn from_name(s: &str) -> Option<Tiling> {
+        match s {
+            "square" => Some(Tiling::Square),
+            "hexagonal" => Some(Tiling::Hexagonal),
+            "triangular" => Some(Tiling::Triangular),
+            "cairo" => Some(Tiling::Cairo),
+            _ => None,
+        }
+    }
+
+    // The vertices of the face, clockwise, on an integer lattice.
+    fn face(self, r: i32, c: i32) -> Vec<(i32, i32)> {
+        match self {
+            Tiling::Square => vec![(c, r), (c + 1, r), (c + 1, r + 1), (c, r + 1)],
+            Tiling::Hexagonal => {
+                // The unit of `x` is the half of the width of a hexagon, and
+                // the unit of `y` is the half of its side.
+                let (x, y) = (2 * c + (r % 2) + 1, 3 * r + 2);
+                vec![(x, y - 2), (x + 1, y - 1), (x + 1, y + 1), (x, y + 2), (x - 1, y + 1),
+                     (x - 1, y - 1)]
+            }
+            Tiling::Triangular => {
+                // The unit of `x` is the half of a side of a triangle.
+                if (r + c) % 2 == 0 {
+                    vec![(c + 1, r), (c + 2, r + 1), (c, r + 1)]
+                } else {
+                    vec![(c, r), (c + 2, r), (c + 1, r + 1)]
+                }
+            }
+            Tiling::Cairo => {
+                // A square is 4 units wide. The segment splitting it runs
+                // from 1 to 3 units across the square, and its ends are
+                // joined to the corners of the square.
+                let (x, y) = (4 * (c / 2), 4 * r);
+                match ((r + c / 2) % 2 == 0, c % 2 == 0) {
+                    (true, true) => {
+                        vec![(x, y), (x + 2, y + 1), (x + 2, y + 3), (x, y + 4), (x - 1, y + 2)]
+                    }
+                    (true, false) => {
+                        vec![(x + 2, y + 1), (x + 4, y), (x + 5, y + 2), (x + 4, y + 4),
+                             (x + 2, y + 3)]
+                    }
+                    (false, true) => {
+                        vec![(x, y), (x + 2, y - 1), (x + 4, y), (x + 3, y + 2), (x + 1, y + 2)]
+                    }
+                    (false, false) => {
+                        vec![(x + 1, y + 2), (x + 3, y + 2), (x + 4, y + 4), (x + 2, y + 5),
+                             (x, y + 4)]
+                    }
+                }
+            }
+        }
+    }
+
+    // The position of the vertex on the lattice, where the sides of the
+    // faces are 1 long.
+    fn position(self, (x, y): (i32, i32)) -> (f64, f64) {
+        let sqrt3 = 3f64.sqrt();
+        match self {
+            Tiling::Square => (x as f64, y as f64),
+            Tiling::Hexagonal => (x as f64 * sqrt3 / 2.0, y as f64 / 2.0),
+            Tiling::Triangular => (x as f64 / 2.0, y as f64 * sqrt3 / 2.0),
+            Tiling::Cairo => {
+                // The segment splitting a square of side 1 is `2 * t` long,
+                // which is the length of the other sides of the pentagons:
+                // `(2 * t)^2 = (1 / 2)^2 + (1 / 2 - t)^2`.
+                let t = (7f64.sqrt() - 1.0) / 6.0;
+                let offsets = [0.0, 0.5 - t, 0.5, 0.5 + t];
+                let pos = |k: i32| {
+                    let m = (k % 4 + 4) % 4;
+                    ((k - m) / 4) as f64 + offsets[m as usize]
+                };
+                (pos(x) / (2.0 * t), pos(y) / (2.0 * t))
+            }
+        }
+    }
+}
+
+/// A face, an edge or a vertex of a board is identified by its index.
+pub type Id = usize;
+
+#[derive(Clone, Debug)]
+struct Face {
+    point: Point,
+    vertices: Vec<Id>,
+    edges: Vec<Id>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct GraphEdge {
+    vertices: (Id, Id),
+    faces: (Option<Id>, Option<Id>),
+}
+
+/// A board of slither link on any planar tiling.
+///
+/// Besides the hints of the faces, a board has the hints of the vertices,
+/// the sets of the numbers and the markers of the faces, the global
+/// constraints of the loop, and the sides of the faces and the edges which
+/// are decided.
+#[derive(Clone, Debug)]
+pub struct Board {
+    tiling: Tiling,
+    size: Size,
+    positions: Vec<(f64, f64)>,
+    lattice: Vec<(i32, i32)>,
+    vertex_edges: Vec<Vec<Id>>,
+    faces: Vec<Face>,
+    face_ids: HashMap<Point, Id>,
+    edges: Vec<GraphEdge>,
+    hints: Vec<Hint>,
+    hint_sets: Vec<Option<HintSet>>,
+    markers: Vec<Option<Marker>>,
+    sides: Vec<Option<Side>>,
+    edge_states: Vec<Option<Edge>>,
+    vertex_hints: Vec<Hint>,
+    loop_length: Option<u32>,
+    area: Option<u32>,
+}
+
+impl Board {
+    /// Creates an empty board of `rows` rows, each of which has `columns`
+    /// faces.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rows` or `columns` is not positive, or `columns` is odd on
+    /// the Cairo tiling.
+    pub fn new(tiling: Tiling, rows: i32, columns: i32) -> Board {
+        assert!(rows > 0 && columns > 0);
+        assert!(tiling != Tiling::Cairo || columns % 2 == 0);
+        let faces = (0..rows)
+                        .flat_map(|r| {
+                            (0..columns).map(move |c| (Point(r, c), tiling.face(r, c)))
+                        })
+                        .collect::<Vec<_>>();
+        Board::from_faces(tiling, Size(rows, columns), &faces)
+    }
+
+    /// Creates a board of the square tiling with the cells of the puzzle.
+    ///
+    /// The masked cells are not the faces of the board, and the hints of the
+    /// vertices and the edges which do not touch any face are dropped.
+    pub fn from_puzzle(puzzle: &Puzzle) -> Board {
+        let faces = puzzle.points()
+                          .filter(|&p| puzzle.is_on_board(p))
+                          .map(|p| (p, Tiling::Square.face(p.0, p.1)))
+                          .collect::<Vec<_>>();
+        let mut board = Board::from_faces(Tiling::Square, puzzle.size(), &faces);
+        for f in 0..board.num_faces() {
+            let p = board.faces[f].point;
+            board.hints[f] = puzzle.hint(p);
+            board.hint_sets[f] = puzzle.hint_set(p);
+            board.markers[f] = puzzle.marker(p);
+            board.sides[f] = puzzle.side(p);
+        }
+        for e in 0..board.num_edges() {
+            board.edge_states[e] = match board.square_edge(e) {
+                (p, true) => puzzle.edge_h(p),
+                (p, false) => puzzle.edge_v(p),
+            };
+        }
+        for v in 0..board.num_vertices() {
+            let (x, y) = board.lattice[v];
+            board.vertex_hints[v] = puzzle.vertex_hint(Point(y, x));
+        }
+        board.loop_length = puzzle.loop_length();
+        board.area = puzzle.area();
+        board
+    }
+
+    /// Writes the sides of the faces and the edges of the board into the
+    /// puzzle which the board is created from by `from_puzzle`.
+    ///
+    /// The masked cells are outside of the loop, and the edges which do not
+    /// touch any face are crosses.
+    pub fn fill_puzzle(&self, puzzle: &mut Puzzle) {
+        for p in puzzle.points() {
+            let side = match self.face_at(p) {
+                Some(f) => self.sides[f],
+                None => Some(Side::Out),
+            };
+            puzzle.set_side(p, side);
+        }
+        for r in 0..(puzzle.row() + 1) {
+            for c in 0..(puzzle.column() + 1) {
+                let p = Point(r, c);
+                if c < puzzle.column() && !puzzle.is_drawn_edge_h(p) {
+                    puzzle.set_edge_h(p, Some(Edge::Cross));
+                }
+                if r < puzzle.row() && !puzzle.is_drawn_edge_v(p) {
+                    puzzle.set_edge_v(p, Some(Edge::Cross));
+                }
+            }
+        }
+        for e in 0..self.num_edges() {
+            match self.square_edge(e) {
+                (p, true) => puzzle.set_edge_h(p, self.edge_states[e]),
+                (p, false) => puzzle.set_edge_v(p, self.edge_states[e]),
+            }
+        }
+    }
+
+    // The point of the edge on the square grid, and whether it is horizontal.
+    fn square_edge(&self, e: Id) -> (Point, bool) {
+        let (v0, v1) = self.edges[e].vertices;
+        let ((x0, y0), (x1, y1)) = (self.lattice[v0], self.lattice[v1]);
+        if y0 == y1 {
+            (Point(y0, cmp::min(x0, x1)), true)
+        } else {
+            (Point(cmp::min(y0, y1), x0), false)
+        }
+    }
+
+    fn from_faces(tiling: Tiling, size: Size, faces: &[(Point, Vec<(i32, i32)>)]) -> Board {
+        let mut vertex_ids = HashMap::new();
+        let mut edge_ids = HashMap::new();
+        let mut board = Board {
+            tiling: tiling,
+            size: size,
+            positions: vec![],
+            lattice: vec![],
+            vertex_edges: vec![],
+            faces: vec![],
+            face_ids: HashMap::new(),
+            edges: vec![],
+            hints: vec![None; faces.len()],
+            hint_sets: vec![None; faces.len()],
+            markers: vec![None; faces.len()],
+            sides: vec![None; faces.len()],
+            edge_states: vec![],
+            vertex_hints: vec![],
+            loop_length: None,
+            area: None,
+        };
+
+        for (f, &(point, ref face)) in faces.iter().enumerate() {
+            let vertices = face.iter()
+                               .map(|&v| {
+                                   let id = vertex_ids.len();
+                                   *vertex_ids.entry(v).or_insert(id)
+                               })
+                               .collect::<Vec<_>>();
+            while board.positions.len() < vertex_ids.len() {
+                board.positions.push((0.0, 0.0));
+                board.lattice.push((0, 0));
+                board.vertex_edges.push(vec![]);
+                board.vertex_hints.push(None);
+            }
+            for (&v, &id) in face.iter().zip(&vertices) {
+                board.positions[id] = tiling.position(v);
+                board.lattice[id] = v;
+            }
+
+            let mut edges = vec![];
+            for i in 0..vertices.len() {
+                let (v0, v1) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+                let key = if v0 < v1 { (v0, v1) } else { (v1, v0) };
+                let id = match edge_ids.get(&key) {
+                    Some(&id) => id,
+                    None => {
+                        let id = board.edges.len();
+                        board.edges.push(GraphEdge {
+                            vertices: key,
+                            faces: (None, None),
+                        });
+                        board.edge_states.push(None);
+                        board.vertex_edges[v0].push(id);
+                        board.vertex_edges[v1].push(id);
+                        let _ = edge_ids.insert(key, id);
+                        id
+                    }
+                };
+                let edge = &mut board.edges[id];
+                if edge.faces.0.is_none() {
+                    edge.faces.0 = Some(f);
+                } else {
+                    edge.faces.1 = Some(f);
+                }
+                edges.push(id);
+            }
+
+            board.faces.push(Face {
+                point: point,
+                vertices: vertices,
+                edges: edges,
+            });
+            let _ = board.face_ids.insert(point, f);
+        }
+
+        board
+    }
+
+    /// Returns the tiling of the board.
+    pub fn tiling(&self) -> Tiling {
+        self.tiling
+    }
+
+    /// Returns the number of the faces.
+    pub fn num_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Returns the number of the edges.
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Returns the number of the vertices.
+    pub fn num_vertices(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Gets a hint of the face.
+    pub fn hint(&self, f: Id) -> Hint {
+        self.hints[f]
+    }
+
+    /// Sets a hint of the face.
+    pub fn set_hint(&mut self, f: Id, hint: Hint) {
+        self.hints[f] = hint;
+    }
+
+    /// Gets a set of the numbers which the hint of the face may be.
+    pub fn hint_set(&self, f: Id) -> Option<HintSet> {
+        self.hint_sets[f]
+    }
+
+    /// Sets a set of the numbers which the hint of the face may be.
+    pub fn set_hint_set(&mut self, f: Id, set: Option<HintSet>) {
+        self.hint_sets[f] = set;
+    }
+
+    /// Gets a marker of the face.
+    pub fn marker(&self, f: Id) -> Option<Marker> {
+        self.markers[f]
+    }
+
+    /// Sets a marker of the face.
+    pub fn set_marker(&mut self, f: Id, marker: Option<Marker>) {
+        self.markers[f] = marker;
+    }
+
+    /// Gets a side of the face.
+    pub fn side(&self, f: Id) -> Option<Side> {
+        self.sides[f]
+    }
+
+    /// Sets a side of the face.
+    pub fn set_side(&mut self, f: Id, side: Option<Side>) {
+        self.sides[f] = side;
+    }
+
+    /// Gets the state of the edge.
+    pub fn edge(&self, e: Id) -> Option<Edge> {
+        self.edge_states[e]
+    }
+
+    /// Sets the state of the edge.
+    pub fn set_edge(&mut self, e: Id, edge: Option<Edge>) {
+        self.edge_states[e] = edge;
+    }
+
+    /// Gets a hint of the vertex, which is the number of the lines at it.
+    pub fn vertex_hint(&self, v: Id) -> Hint {
+        self.vertex_hints[v]
+    }
+
+    /// Sets a hint of the vertex.
+    pub fn set_vertex_hint(&mut self, v: Id, hint: Hint) {
+        self.vertex_hints[v] = hint;
+    }
+
+    /// Gets the number of the lines of the loop, if it is given.
+    pub fn loop_length(&self) -> Option<u32> {
+        self.loop_length
+    }
+
+    /// Sets the number of the lines of the loop.
+    pub fn set_loop_length(&mut self, length: Option<u32>) {
+        self.loop_length = length;
+    }
+
+    /// Gets the number of the faces inside of the loop, if it is given.
+    pub fn area(&self) -> Option<u32> {
+        self.area
+    }
+
+    /// Sets the number of the faces inside of the loop.
+    pub fn set_area(&mut self, area: Option<u32>) {
+        self.area = area;
+    }
+
+    /// Returns the numbers of the rows and the columns of the faces, as
+    /// they are written in the text.
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the row and the column of the face in the text.
+    pub fn face_point(&self, f: Id) -> Point {
+        self.faces[f].point
+    }
+
+    /// Returns the face at the row and the column in the text, if any.
+    pub fn face_at(&self, p: Point) -> Option<Id> {
+        self.face_ids.get(&p).cloned()
+    }
+
+    /// Returns the vertices of the face, clockwise.
+    pub fn face_vertices(&self, f: Id) -> &[Id] {
+        &self.faces[f].vertices
+    }
+
+    /// Returns the edges of the face, clockwise.
+    pub fn face_edges(&self, f: Id) -> &[Id] {
+        &self.faces[f].edges
+    }
+
+    /// Returns the faces on both sides of the edge. `None` is the outside
+    /// of the board.
+    pub fn edge_faces(&self, e: Id) -> (Option<Id>, Option<Id>) {
+        self.edges[e].faces
+    }
+
+    /// Returns the vertices at both ends of the edge.
+    pub fn edge_vertices(&self, e: Id) -> (Id, Id) {
+        self.edges[e].vertices
+    }
+
+    /// Returns the edges at the vertex.
+    pub fn vertex_edges(&self, v: Id) -> &[Id] {
+        &self.vertex_edges[v]
+    }
+
+    /// Returns the position of the vertex, where the sides of the faces are
+    /// 1 long and `y` grows downwards.
+    pub fn position(&self, v: Id) -> (f64, f64) {
+        self.positions[v]
+    }
+
+    /// Returns the vertices of the loop drawn by the line edges, without
+    /// repeating the first one.
+    ///
+    /// Returns `None` if the lines are not a single loop.
+    pub fn loop_path(&self, lines: &[bool]) -> Option<Vec<Id>> {
+        let degree = |v: Id| self.vertex_edges[v].iter().filter(|&&e| lines[e]).count();
+        if (0..self.num_vertices()).any(|v| degree(v) != 0 && degree(v) != 2) {
+            return None;
+        }
+
+        let first = match (0..self.num_edges()).find(|&e| lines[e]) {
+            Some(e) => e,
+            None => return None,
+        };
+        let (start, mut v) = self.edges[first].vertices;
+        let mut path = vec![start];
+        let mut prev = first;
+        while v != start {
+            path.push(v);
+            prev = *self.vertex_edges[v]
+                        .iter()
+                        .find(|&&e| lines[e] && e != prev)
+                        .unwrap();
+            let (v0, v1) = self.edges[prev].vertices;
+            v = if v0 == v { v1 } else { v0 };
+        }
+
+        if path.len() != lines.iter().filter(|&&l| l).count() {
+            return None;
+        }
+        Some(path)
+    }
+}
+
+/// An error type which is returned from parsing a string into board.
+#[derive(Copy, Clone, Debug)]
+pub struct ParseBoardError {
+    kind: BoardErrorKind,
+    span: Option<Span>,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum BoardErrorKind {
+    Empty,
+    UnknownTiling,
+    LengthMismatch,
+    OddLength,
+    InvalidHint,
+}
+
+impl Error for ParseBoardError {
+    fn description(&self) -> &str {
+        use self::BoardErrorKind::*;
+        match self.kind {
+            Empty => "cannot parse board from empty string",
+            UnknownTiling => "unknown tiling found in string",
+            LengthMismatch => "the length of lines mismatch",
+            OddLength => "the rows of cairo tiling have an odd number of faces",
+            InvalidHint => "invalid hint found in string",
+        }
+    }
+}
+
+impl fmt::Display for ParseBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{} at {}", self.description(), span),
+            None => self.description().fmt(f),
+        }
+    }
+}
+
+impl ParseBoardError {
+    fn empty() -> ParseBoardError {
+        ParseBoardError {
+            kind: BoardErrorKind::Empty,
+            span: None,
+        }
+    }
+    fn unknown_tiling(span: Span) -> ParseBoardError {
+        ParseBoardError {
+            kind: BoardErrorKind::UnknownTiling,
+            span: Some(span),
+        }
+    }
+    fn length_mismatch(span: Span) -> ParseBoardError {
+        ParseBoardError {
+            kind: BoardErrorKind::LengthMismatch,
+            span: Some(span),
+        }
+    }
+    fn odd_length(span: Span) -> ParseBoardError {
+        ParseBoardError {
+            kind: BoardErrorKind::OddLength,
+            span: Some(span),
+        }
+    }
+    fn invalid_hint(span: Span) -> ParseBoardError {
+        ParseBoardError {
+            kind: BoardErrorKind::InvalidHint,
+            span: Some(span),
+        }
+    }
+
+    /// Returns the position of the error in the parsed string, if known.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+}
+
+/// Returns `true` if the string starts with the name of a tiling.
+pub fn is_board(s: &str) -> bool {
+    s.lines()
+     .next()
+     .and_then(|l| Tiling::from_name(l.trim()))
+     .is_some()
+}
+
+impl FromStr for Board {
+    type Err = ParseBoardError;
+
+    fn from_str(s: &str) -> Result<Board, ParseBoardError> {
+        let lines = s.lines().collect::<Vec<_>>();
+        let name = match lines.first() {
+            Some(name) => name.trim(),
+            None => return Err(ParseBoardError::empty()),
+        };
+        let tiling = match Tiling::from_name(name) {
+            Some(tiling) => tiling,
+            None => return Err(ParseBoardError::unknown_tiling(Span::new(0, 0, name.len()))),
+        };
+
+        let rows = lines[1..]
+                       .iter()
+                       .map(|l| l.trim_right().chars().collect::<Vec<_>>())
+                       .take_while(|l| !l.is_empty())
+                       .collect::<Vec<_>>();
+        if rows.is_empty() {
+            return Err(ParseBoardError::empty());
+        }
+        let columns = rows[0].len();
+        if let Some(r) = (1..rows.len()).find(|&r| rows[r].len() != columns) {
+            return Err(ParseBoardError::length_mismatch(Span::new(r + 1, 0, rows[r].len())));
+        }
+        if tiling == Tiling::Cairo && columns % 2 != 0 {
+            return Err(ParseBoardError::odd_length(Span::new(1, 0, columns)));
+        }
+
+        let mut board = Board::new(tiling, rows.len() as i32, columns as i32);
+        for (r, row) in rows.iter().enumerate() {
+            for (c, &ch) in row.iter().enumerate() {
+                let hint = match ch {
+                    '0'...'9' => Some(ch as u8 - b'0'),
+                    '_' | '-' => None,
+                    _ => return Err(ParseBoardError::invalid_hint(Span::new(r + 1, c, 1))),
+                };
+                board.set_hint(r * columns + c, hint);
+            }
+        }
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Board, Tiling};
+    use geom::Point;
+    use puzzle::{Edge, Marker, Puzzle, Side};
+
+    #[test]
+    fn topology() {
+        let board = Board::new(Tiling::Square, 2, 3);
+        assert_eq!((6, 17, 12),
+                   (board.num_faces(), board.num_edges(), board.num_vertices()));
+        assert_eq!(4, board.face_edges(0).len());
+
+        let board = Board::new(Tiling::Hexagonal, 2, 2);
+        assert_eq!((4, 19, 16),
+                   (board.num_faces(), board.num_edges(), board.num_vertices()));
+        assert_eq!(6, board.face_edges(0).len());
+
+        let board = Board::new(Tiling::Triangular, 2, 3);
+        assert_eq!((6, 12, 7),
+                   (board.num_faces(), board.num_edges(), board.num_vertices()));
+        assert_eq!(3, board.face_edges(0).len());
+
+        let board = Board::new(Tiling::Cairo, 2, 4);
+        assert_eq!((8, 28, 21),
+                   (board.num_faces(), board.num_edges(), board.num_vertices()));
+        assert_eq!(5, board.face_edges(0).len());
+
+        for &tiling in &[Tiling::Square, Tiling::Hexagonal, Tiling::Triangular, Tiling::Cairo] {
+            let board = Board::new(tiling, 4, 4);
+            assert_eq!(1 + board.num_edges(), board.num_faces() + board.num_vertices());
+            for e in 0..board.num_edges() {
+                let (f0, f1) = board.edge_faces(e);
+                assert!(f0.is_some() || f1.is_some());
+
+                let (v0, v1) = board.edge_vertices(e);
+                let ((x0, y0), (x1, y1)) = (board.position(v0), board.position(v1));
+                let len = ((x0 - x1).powi(2) + (y0 - y1).powi(2)).sqrt();
+                assert!((len - 1.0).abs() < 1e-9, "{:?} {}", tiling, len);
+            }
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let board = "hexagonal\n3_\n_2\n".parse::<Board>().unwrap();
+        assert_eq!(Tiling::Hexagonal, board.tiling());
+        assert_eq!(4, board.num_faces());
+        assert_eq!(Some(3), board.hint(0));
+        assert_eq!(None, board.hint(1));
+        assert_eq!(Some(2), board.hint(3));
+
+        assert!(super::is_board("triangular\n1\n"));
+        assert!(!super::is_board("+-+\n|1|\n+-+\n"));
+        assert!("".parse::<Board>().is_err());
+        assert!("octagonal\n1\n".parse::<Board>().is_err());
+        assert!("square\n12\n1\n".parse::<Board>().is_err());
+        assert!("square\n1a\n".parse::<Board>().is_err());
+        assert!("cairo\n1_1\n".parse::<Board>().is_err());
+        let board = "cairo\n1_\n_2\n".parse::<Board>().unwrap();
+        assert_eq!(Point(1, 1), board.face_point(3));
+        assert_eq!(Some(2), board.hint(3));
+    }
+
+    #[test]
+    fn from_puzzle() {
+        let puzzle = "#3\n3_\n".parse::<Puzzle>().unwrap();
+        let board = Board::from_puzzle(&puzzle);
+        assert_eq!(3, board.num_faces());
+        assert_eq!(Some(3), board.hint(0));
+        assert_eq!(None, board.hint(2));
+        assert_eq!(None, board.face_at(Point(0, 0)));
+        assert_eq!(Some(0), board.face_at(Point(0, 1)));
+
+        let puzzle = "+-1 +\n|s  x\n+ + +\n".parse::<Puzzle>().unwrap();
+        let mut board = Board::from_puzzle(&puzzle);
+        assert_eq!(Some(Marker::Sheep), board.marker(0));
+        let top = board.face_edges(0)[0];
+        let right = board.face_edges(1)[1];
+        assert_eq!(Some(Edge::Line), board.edge(top));
+        assert_eq!(Some(Edge::Cross), board.edge(right));
+        let v = board.edge_vertices(top).1;
+        assert_eq!(Some(1), board.vertex_hint(v));
+
+        board.set_side(1, Some(Side::In));
+        board.set_edge(right, Some(Edge::Line));
+        let mut filled = puzzle.clone();
+        board.fill_puzzle(&mut filled);
+        assert_eq!(Some(Side::In), filled.side(Point(0, 1)));
+        assert_eq!(Some(Edge::Line), filled.edge_v(Point(0, 2)));
+        assert_eq!(Some(Edge::Line), filled.edge_h(Point(0, 0)));
+    }
+
+    #[test]
+    fn loop_path() {
+        let board = Board::new(Tiling::Square, 1, 2);
+        let mut lines = vec![false; board.num_edges()];
+        assert_eq!(None, board.loop_path(&lines));
+
+        for &e in board.face_edges(0) {
+            lines[e] = true;
+        }
+        assert_eq!(Some(4), board.loop_path(&lines).map(|p| p.len()));
+
+        for &e in board.face_edges(1) {
+            lines[e] = !lines[e];
+        }
+        assert_eq!(Some(6), board.loop_path(&lines).map(|p| p.len()));
+
+        let board = Board::new(Tiling::Square, 1, 3);
+        let mut lines = vec![false; board.num_edges()];
+        for &e in board.face_edges(0).iter().chain(board.face_edges(2)) {
+            lines[e] = true;
+        }
+        assert_eq!(None, board.loop_path(&lines));
+    }
+}
@@ -0,0 +1,276 @@
+// Copyright (c) 2016 srither-core developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Importing puzzles from the pages of [janko.at](http://www.janko.at/Raetsel/Slitherlink).
+//!
+//! The puzzle data of a page is the `problem` block, a grid of the hints
+//! (`0`-`4`, or `-` for no hint), followed by the `solution` block, a grid of
+//! the cells' sides (`x` or `1` for the cells inside the loop, `-` or `0` for
+//! the cells outside).
+
+use std::error::Error;
+use std::fmt;
+
+use geom::{Geom, Move, Point, Size};
+use puzzle::{Edge, Puzzle, Side};
+
+/// An error type which is returned from parsing a janko.at page into puzzle.
+#[derive(Copy, Clone, Debug)]
+pub struct ParseJankoError {
+    kind: JankoErrorKind,
+}
+
+/// janko.at page parse result.
+pub type ParseJankoResult<T> = Result<T, ParseJankoError>;
+
+#[derive(Copy, Clone, Debug)]
+enum JankoErrorKind {
+    NoProblem,
+    LengthMismatch,
+    InvalidHint,
+    InvalidSolution,
+}
+
+impl Error for ParseJankoError {
+    fn description(&self) -> &str {
+        use self::JankoErrorKind::*;
+        match self.kind {
+            NoProblem => "cannot found problem block in string",
+            LengthMismatch => "the length of lines are not same",
+            InvalidHint => "invalid hint found in problem block",
+            InvalidSolution => "invalid cell found in solution block",
+        }
+    }
+}
+
+impl fmt::Display for ParseJankoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(f)
+    }
+}
+
+impl ParseJankoError {
+    fn no_problem() -> ParseJankoError {
+        ParseJankoError { kind: JankoErrorKind::NoProblem }
+    }
+    fn length_mismatch() -> ParseJankoError {
+        ParseJankoError { kind: JankoErrorKind::LengthMismatch }
+    }
+    fn invalid_hint() -> ParseJankoError {
+        ParseJankoError { kind: JankoErrorKind::InvalidHint }
+    }
+    fn invalid_solution() -> ParseJankoError {
+        ParseJankoError { kind: JankoErrorKind::InvalidSolution }
+    }
+}
+
+fn is_block_start(line: &str, name: &str) -> bool {
+    let line = line.trim();
+    line == name || (line.starts_with('[') && line.ends_with(']') && &line[1..line.len() - 1] == name)
+}
+
+/// Returns true if the string contains the problem block of janko.at page.
+pub fn is_page(s: &str) -> bool {
+    s.lines().any(|l| is_block_start(l, "problem"))
+}
+
+/// Parses a janko.at page, or its `problem` and `solution` blocks, into
+/// puzzle.
+///
+/// Returns the puzzle and the published solution, if the page has one.
+pub fn parse_page(s: &str) -> ParseJankoResult<(Puzzle, Option<Puzzle>)> {
+    use self::ParseJankoError as Error;
+
+    let mut lines = s.lines().skip_while(|l| !is_block_start(l, "problem"));
+    if lines.next().is_none() {
+        return Err(Error::no_problem());
+    }
+
+    let mut hints = vec![];
+    for line in lines.by_ref() {
+        let line = line.trim();
+        match line.chars().next() {
+            Some(c) if c.is_digit(10) || c == '-' || c == '.' => {}
+            _ => break,
+        }
+        let row = try!(line.split_whitespace()
+                           .map(|s| {
+                               match s {
+                                   "0" => Ok(Some(0)),
+                                   "1" => Ok(Some(1)),
+                                   "2" => Ok(Some(2)),
+                                   "3" => Ok(Some(3)),
+                                   "4" => Ok(Some(4)),
+                                   "-" | "." => Ok(None),
+                                   _ => Err(Error::invalid_hint()),
+                               }
+                           })
+                           .collect::<ParseJankoResult<Vec<_>>>());
+        hints.push(row);
+    }
+
+    let size = try!(grid_size(&hints));
+    let mut puzzle = Puzzle::new(size);
+    for (r, row) in hints.into_iter().enumerate() {
+        for (c, hint) in row.into_iter().enumerate() {
+            puzzle.set_hint(Point(r as i32, c as i32), hint);
+        }
+    }
+
+    let mut lines = s.lines().skip_while(|l| !is_block_start(l, "solution"));
+    if lines.next().is_none() {
+        return Ok((puzzle, None));
+    }
+
+    let mut sides = vec![];
+    for line in lines.take(size.0 as usize) {
+        let row = try!(line.split_whitespace()
+                           .map(|s| {
+                               match s {
+                                   "x" | "1" => Ok(Side::In),
+                                   "-" | "0" => Ok(Side::Out),
+                                   _ => Err(Error::invalid_solution()),
+                               }
+                           })
+                           .collect::<ParseJankoResult<Vec<_>>>());
+        sides.push(row);
+    }
+    if sides.len() != size.0 as usize || sides.iter().any(|row| row.len() != size.1 as usize) {
+        return Err(Error::length_mismatch());
+    }
+
+    let mut solution = puzzle.clone();
+    for (r, row) in sides.into_iter().enumerate() {
+        for (c, side) in row.into_iter().enumerate() {
+            solution.set_side(Point(r as i32, c as i32), Some(side));
+        }
+    }
+    fill_edges(&mut solution);
+
+    Ok((puzzle, Some(solution)))
+}
+
+fn grid_size<T>(grid: &[Vec<T>]) -> ParseJankoResult<Size> {
+    let rows = grid.len();
+    let cols = grid.first().map_or(0, |row| row.len());
+    if rows == 0 || cols == 0 {
+        return Err(ParseJankoError::no_problem());
+    }
+    if grid[1..].iter().any(|row| row.len() != cols) {
+        return Err(ParseJankoError::length_mismatch());
+    }
+    Ok(Size(rows as i32, cols as i32))
+}
+
+fn fill_edges(puzzle: &mut Puzzle) {
+    let edge = |puzzle: &Puzzle, p0: Point, p1: Point| {
+        if puzzle.side(p0) == puzzle.side(p1) {
+            Some(Edge::Cross)
+        } else {
+            Some(Edge::Line)
+        }
+    };
+
+    for r in 0..(puzzle.row() + 1) {
+        for c in 0..puzzle.column() {
+            let p = Point(r, c);
+            let e = edge(puzzle, p, p + Move::UP);
+            puzzle.set_edge_h(p, e);
+        }
+    }
+    for r in 0..puzzle.row() {
+        for c in 0..(puzzle.column() + 1) {
+            let p = Point(r, c);
+            let e = edge(puzzle, p, p + Move::LEFT);
+            puzzle.set_edge_v(p, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+    use super::{ParseJankoError, ParseJankoResult};
+    use geom::{Geom, Point, Size};
+    use puzzle::{Edge, Puzzle, Side};
+
+    fn check_error<T>(result: ParseJankoResult<T>, error: ParseJankoError) {
+        match result {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => assert_eq!(e.description(), error.description()),
+        }
+    }
+
+    #[test]
+    fn parse_page() {
+        let input = "<html>
+<script>
+begin
+puzzle slitherlink
+size 3
+problem
+3 - -
+- 2 -
+- - 3
+solution
+x x -
+- x x
+- - x
+end
+</script>
+</html>
+";
+        let (puzzle, solution) = super::parse_page(input).unwrap();
+        assert_eq!(Size(3, 3), puzzle.size());
+        assert_eq!(Some(3), puzzle.hint(Point(0, 0)));
+        assert_eq!(None, puzzle.hint(Point(0, 1)));
+        assert_eq!(Some(2), puzzle.hint(Point(1, 1)));
+        assert_eq!(Some(3), puzzle.hint(Point(2, 2)));
+        assert_eq!(None, puzzle.side(Point(0, 0)));
+
+        let solution = solution.unwrap();
+        let expected = "+--+--+xx+
+|3ix i| ox
++--+xx+--+
+x o|2ix i|
++xx+--+xx+
+x ox o|3i|
++xx+xx+--+
+";
+        assert_eq!(expected, solution.to_string());
+        assert_eq!(Some(Side::In), solution.side(Point(0, 0)));
+        assert_eq!(Some(Side::Out), solution.side(Point(2, 0)));
+        assert_eq!(Some(Edge::Line), solution.edge_h(Point(0, 0)));
+        assert_eq!(Some(Edge::Cross), solution.edge_v(Point(0, 1)));
+    }
+
+    #[test]
+    fn parse_problem_only() {
+        let (puzzle, solution) = super::parse_page("problem\n1 -\n- 0\n").unwrap();
+        let mut expected = Puzzle::new(Size(2, 2));
+        expected.set_hint(Point(0, 0), Some(1));
+        expected.set_hint(Point(1, 1), Some(0));
+        assert_eq!(expected, puzzle);
+        assert!(solution.is_none());
+    }
+
+    #[test]
+    fn parse_invalid() {
+        check_error(super::parse_page("1 -\n- 0\n"), ParseJankoError::no_problem());
+        check_error(super::parse_page("problem\nend\n"),
+                    ParseJankoError::no_problem());
+        check_error(super::parse_page("problem\n1 -\n-\n"),
+                    ParseJankoError::length_mismatch());
+        check_error(super::parse_page("problem\n1 5\n"),
+                    ParseJankoError::invalid_hint());
+        check_error(super::parse_page("problem\n1 -\nsolution\nx y\n"),
+                    ParseJankoError::invalid_solution());
+        check_error(super::parse_page("problem\n1 -\n- -\nsolution\nx -\n"),
+                    ParseJankoError::length_mismatch());
+    }
+}
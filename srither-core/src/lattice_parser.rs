@@ -8,14 +8,17 @@
 
 //! Parsing a lattice strings.
 
-use std::{fmt, iter};
+use std::{cmp, fmt, iter};
+use std::ops::Range;
 use std::error::Error;
 use geom::Point;
+use diagnostic::Span;
 
 /// An error type which is returned from parsing a string into lattice.
 #[derive(Copy, Clone, Debug)]
 pub struct ParseLatticeError {
     kind: LatticeErrorKind,
+    span: Option<Span>,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -33,13 +36,24 @@ impl Error for ParseLatticeError {
 
 impl fmt::Display for ParseLatticeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.description().fmt(f)
+        match self.span {
+            Some(span) => write!(f, "{} at {}", self.description(), span),
+            None => self.description().fmt(f),
+        }
     }
 }
 
 impl ParseLatticeError {
-    fn invalid_lattice_point() -> ParseLatticeError {
-        ParseLatticeError { kind: LatticeErrorKind::InvalidLatticePoint }
+    fn invalid_lattice_point(span: Span) -> ParseLatticeError {
+        ParseLatticeError {
+            kind: LatticeErrorKind::InvalidLatticePoint,
+            span: Some(span),
+        }
+    }
+
+    /// Returns the position of the error in the parsed lines, if known.
+    pub fn span(&self) -> Option<Span> {
+        self.span
     }
 }
 
@@ -53,32 +67,43 @@ pub struct LatticeParser<'a> {
 
 impl<'a> LatticeParser<'a> {
     /// Creates a lattice parser from lines of strings.
+    ///
+    /// A lattice point is `+` or a digit. The lines and the columns of the
+    /// lattice are found by `+`, and by the digits in line with the other
+    /// lattice points, so that a line or a column whose points are all digits
+    /// is found too. If no line has `+`, the first line is a line of the
+    /// lattice.
     pub fn from_lines(lines: &'a [Vec<char>]) -> Result<LatticeParser<'a>, ParseLatticeError> {
         use self::ParseLatticeError as Error;
 
-        let rows = lines.iter()
-                        .enumerate()
-                        .filter(|&(_, cs)| cs.iter().any(|&c| c == '+'))
-                        .map(|(i, _)| i)
-                        .collect::<Vec<_>>();
-        let cols = lines[rows[0]]
-                       .iter()
-                       .enumerate()
-                       .filter(|&(_, &c)| c == '+')
-                       .map(|(i, _)| i)
+        let is_point = |c: char| c == '+' || c.is_digit(10);
+        let is_digit_at = |r: usize, c: usize| lines[r].get(c).map_or(false, |ch| ch.is_digit(10));
+        let mut seeds = (0..lines.len())
+                            .filter(|&r| lines[r].iter().any(|&c| c == '+'))
+                            .collect::<Vec<_>>();
+        if seeds.is_empty() && !lines.is_empty() {
+            seeds.push(0);
+        }
+        let width = lines.iter().map(|cs| cs.len()).max().unwrap_or(0);
+        let cols = (0..width)
+                       .filter(|&c| {
+                           seeds.iter().any(|&r| lines[r].get(c) == Some(&'+')) ||
+                           seeds.iter().all(|&r| is_digit_at(r, c))
+                       })
+                       .collect::<Vec<_>>();
+        let rows = (0..lines.len())
+                       .filter(|&r| {
+                           seeds.contains(&r) ||
+                           !cols.is_empty() && cols.iter().all(|&c| is_digit_at(r, c))
+                       })
                        .collect::<Vec<_>>();
 
         // check all rows have same lattice points
-        for &r in &rows[1..] {
-            let cur_rows = lines[r]
-                               .iter()
-                               .enumerate()
-                               .filter(|&(_, &c)| c == '+')
-                               .map(|(i, _)| i);
-
-            let count = cur_rows.zip(&cols).filter(|&(p, &q)| p == q).count();
-            if count != cols.len() {
-                return Err(Error::invalid_lattice_point());
+        for &r in &rows {
+            let missing = cols.iter().find(|&&c| !lines[r].get(c).map_or(false, |&ch| is_point(ch)));
+            if let Some(&column) = missing {
+                // Points to the first lattice point which is misplaced or missing.
+                return Err(Error::invalid_lattice_point(Span::new(r, column, 1)));
             }
         }
 
@@ -101,6 +126,76 @@ impl<'a> LatticeParser<'a> {
         self.cols.len()
     }
 
+    /// Returns the position of the contents of the cell in the lines.
+    ///
+    /// The span covers the non-blank characters of the first line of the
+    /// cell which has any, or the whole first line of the cell if it is
+    /// blank.
+    pub fn cell_span(&self, p: Point) -> Span {
+        let (r, c) = (p.0 as usize, p.1 as usize);
+        let (rs, re) = (self.rows[r], self.rows[r + 1]);
+        let (cs, ce) = (self.cols[c], self.cols[c + 1]);
+        self.area_span(rs + 1..re, cs + 1..ce)
+    }
+
+    /// Returns the character at the lattice point, which is `+` or a digit.
+    pub fn point(&self, p: Point) -> char {
+        self.mat[self.rows[p.0 as usize]][self.cols[p.1 as usize]]
+    }
+
+    /// Returns the position of the lattice point in the lines.
+    pub fn point_span(&self, p: Point) -> Span {
+        Span::new(self.rows[p.0 as usize], self.cols[p.1 as usize], 1)
+    }
+
+    /// Returns the position of the horizontal edge in the lines.
+    pub fn h_edge_span(&self, p: Point) -> Span {
+        let (r, c) = (p.0 as usize, p.1 as usize);
+        let (cs, ce) = (self.cols[c], self.cols[c + 1]);
+        self.area_span(self.rows[r]..self.rows[r] + 1, cs + 1..ce)
+    }
+
+    /// Returns the position of the vertical edge in the lines.
+    pub fn v_edge_span(&self, p: Point) -> Span {
+        let (r, c) = (p.0 as usize, p.1 as usize);
+        let (rs, re) = (self.rows[r], self.rows[r + 1]);
+        self.area_span(rs + 1..re, self.cols[c]..self.cols[c] + 1)
+    }
+
+    /// Returns the position of the first non-blank text outside of the
+    /// lattice, if any.
+    pub fn outside_text(&self) -> Option<Span> {
+        let (rs, re) = (self.rows[0], self.rows[self.rows.len() - 1]);
+        let (cs, ce) = (self.cols[0], self.cols[self.cols.len() - 1]);
+        for (i, row) in self.mat.iter().enumerate() {
+            let outside = |j: usize| i < rs || i > re || j < cs || j > ce;
+            let found = row.iter()
+                           .enumerate()
+                           .position(|(j, &c)| c != ' ' && outside(j));
+            if let Some(first) = found {
+                let len = row[first..]
+                              .iter()
+                              .take_while(|&&c| c != ' ')
+                              .count();
+                return Some(Span::new(i, first, len));
+            }
+        }
+        None
+    }
+
+    fn area_span(&self, lines: Range<usize>, cols: Range<usize>) -> Span {
+        for line in lines.clone() {
+            let row = &self.mat[line];
+            let filled = (cols.start..cmp::min(cols.end, row.len()))
+                             .filter(|&i| row[i] != ' ')
+                             .collect::<Vec<_>>();
+            if let (Some(&first), Some(&last)) = (filled.first(), filled.last()) {
+                return Span::new(line, first, last - first + 1);
+            }
+        }
+        Span::new(lines.start, cols.start, cols.end - cols.start)
+    }
+
     /// Returns an iterator iterating the vertical edges of the lattice.
     #[inline]
     pub fn v_edges(&self) -> VEdges {
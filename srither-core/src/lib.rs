@@ -29,7 +29,18 @@
 #![cfg_attr(feature="dev", warn(string_add_assign))]
 
 extern crate board_game_geom;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 
 pub use board_game_geom as geom;
+pub mod collection;
+pub mod diagnostic;
+pub mod graph;
+pub mod janko;
 pub mod lattice_parser;
+pub mod loopy;
 pub mod puzzle;
+pub mod pzpr;
+pub mod tikz;
@@ -0,0 +1,589 @@
+// Copyright (c) 2016 srither-core developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Conversion between puzzles and the formats of Simon Tatham's Loopy.
+//!
+//! Only the square grid (`t0`) is supported. A game ID such as `7x7t0:02a3b`
+//! holds the clues of the faces in row-major order, where `a`-`z` stand for
+//! 1-26 faces without clue. A save file additionally holds the moves, which
+//! set the state of the edges numbered in the order Loopy builds its grid.
+
+use std::error::Error;
+use std::fmt;
+
+use geom::{Geom, Move, Point, Size};
+use puzzle::{Edge, Puzzle};
+
+const GAME_NAME: &'static str = "Loopy";
+const SAVE_HEADER: &'static str = "Simon Tatham's Portable Puzzle Collection";
+
+/// An error type which is returned from parsing a Loopy string into puzzle.
+#[derive(Copy, Clone, Debug)]
+pub struct ParseLoopyError {
+    kind: LoopyErrorKind,
+}
+
+/// Loopy parse result.
+pub type ParseLoopyResult<T> = Result<T, ParseLoopyError>;
+
+#[derive(Copy, Clone, Debug)]
+enum LoopyErrorKind {
+    InvalidParams,
+    UnsupportedGrid,
+    InvalidDesc,
+    InvalidRecord,
+    UnsupportedGame,
+    InvalidMove,
+}
+
+impl Error for ParseLoopyError {
+    fn description(&self) -> &str {
+        use self::LoopyErrorKind::*;
+        match self.kind {
+            InvalidParams => "invalid game parameters found in string",
+            UnsupportedGrid => "grid types other than square are not supported",
+            InvalidDesc => "invalid game description found in string",
+            InvalidRecord => "invalid record found in save file",
+            UnsupportedGame => "the game is not loopy",
+            InvalidMove => "invalid move found in save file",
+        }
+    }
+}
+
+impl fmt::Display for ParseLoopyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(f)
+    }
+}
+
+impl ParseLoopyError {
+    fn invalid_params() -> ParseLoopyError {
+        ParseLoopyError { kind: LoopyErrorKind::InvalidParams }
+    }
+    fn unsupported_grid() -> ParseLoopyError {
+        ParseLoopyError { kind: LoopyErrorKind::UnsupportedGrid }
+    }
+    fn invalid_desc() -> ParseLoopyError {
+        ParseLoopyError { kind: LoopyErrorKind::InvalidDesc }
+    }
+    fn invalid_record() -> ParseLoopyError {
+        ParseLoopyError { kind: LoopyErrorKind::InvalidRecord }
+    }
+    fn unsupported_game() -> ParseLoopyError {
+        ParseLoopyError { kind: LoopyErrorKind::UnsupportedGame }
+    }
+    fn invalid_move() -> ParseLoopyError {
+        ParseLoopyError { kind: LoopyErrorKind::InvalidMove }
+    }
+}
+
+/// An error type which is returned from encoding a puzzle into a Loopy
+/// string, if the puzzle has what Loopy cannot hold.
+#[derive(Copy, Clone, Debug)]
+pub struct EncodeLoopyError {
+    kind: EncodeLoopyErrorKind,
+}
+
+/// Loopy encode result.
+pub type EncodeLoopyResult<T> = Result<T, EncodeLoopyError>;
+
+#[derive(Copy, Clone, Debug)]
+enum EncodeLoopyErrorKind {
+    Masked,
+    VertexHint,
+    Marker,
+    HintSet,
+    Constraint,
+}
+
+impl Error for EncodeLoopyError {
+    fn description(&self) -> &str {
+        use self::EncodeLoopyErrorKind::*;
+        match self.kind {
+            Masked => "masked cells cannot be encoded into Loopy",
+            VertexHint => "hints of the vertices cannot be encoded into Loopy",
+            Marker => "sheep and wolves cannot be encoded into Loopy",
+            HintSet => "sets of the numbers cannot be encoded into Loopy",
+            Constraint => "the loop length and the area cannot be encoded into Loopy",
+        }
+    }
+}
+
+impl fmt::Display for EncodeLoopyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(f)
+    }
+}
+
+impl EncodeLoopyError {
+    fn masked() -> EncodeLoopyError {
+        EncodeLoopyError { kind: EncodeLoopyErrorKind::Masked }
+    }
+    fn vertex_hint() -> EncodeLoopyError {
+        EncodeLoopyError { kind: EncodeLoopyErrorKind::VertexHint }
+    }
+    fn marker() -> EncodeLoopyError {
+        EncodeLoopyError { kind: EncodeLoopyErrorKind::Marker }
+    }
+    fn hint_set() -> EncodeLoopyError {
+        EncodeLoopyError { kind: EncodeLoopyErrorKind::HintSet }
+    }
+    fn constraint() -> EncodeLoopyError {
+        EncodeLoopyError { kind: EncodeLoopyErrorKind::Constraint }
+    }
+}
+
+/// Returns true if the string looks like a Loopy game ID.
+pub fn is_game_id(s: &str) -> bool {
+    let s = s.trim();
+    match s.find(':') {
+        Some(i) => !s.contains(char::is_whitespace) && parse_params(&s[..i]).is_ok(),
+        None => false,
+    }
+}
+
+/// Parses a Loopy game ID (`<params>:<description>`) into puzzle.
+pub fn parse_game_id(s: &str) -> ParseLoopyResult<Puzzle> {
+    let s = s.trim();
+    match s.find(':') {
+        Some(i) => {
+            let size = try!(parse_params(&s[..i]));
+            parse_desc(size, &s[i + 1..])
+        }
+        None => Err(ParseLoopyError::invalid_params()),
+    }
+}
+
+/// Encodes the hints of the puzzle into Loopy game ID.
+pub fn to_game_id(puzzle: &Puzzle) -> EncodeLoopyResult<String> {
+    try!(check_encodable(puzzle));
+    Ok(format!("{}:{}", params(puzzle), desc(puzzle)))
+}
+
+/// Returns true if the string looks like a Loopy save file.
+pub fn is_save(s: &str) -> bool {
+    s.trim_left().starts_with("SAVEFILE:")
+}
+
+/// Parses a Loopy save file into puzzle.
+///
+/// The line and cross marks of the current position are also read.
+pub fn parse_save(s: &str) -> ParseLoopyResult<Puzzle> {
+    use self::ParseLoopyError as Error;
+
+    let records = try!(parse_records(s.trim_left()));
+    let get = |key: &str| {
+        records.iter()
+               .find(|&&(k, _)| k == key)
+               .map(|&(_, v)| v)
+               .ok_or(Error::invalid_record())
+    };
+
+    if try!(get("GAME")) != GAME_NAME {
+        return Err(Error::unsupported_game());
+    }
+    let size = try!(parse_params(try!(get("PARAMS"))));
+    let mut puzzle = try!(parse_desc(size, try!(get("DESC"))));
+    let initial = puzzle.clone();
+
+    // The moves after the current position are the history to redo.
+    let pos = match get("STATEPOS").map(|v| v.parse::<usize>()) {
+        Ok(Ok(n)) if n > 0 => n,
+        Ok(_) => return Err(Error::invalid_record()),
+        Err(_) => 1,
+    };
+    let moves = records.iter().filter(|&&(k, _)| k == "MOVE" || k == "SOLVE" || k == "RESTART");
+    for &(key, value) in moves.take(pos - 1) {
+        if key == "RESTART" {
+            puzzle = initial.clone();
+        } else {
+            try!(apply_move(&mut puzzle, value));
+        }
+    }
+
+    Ok(puzzle)
+}
+
+/// Encodes the puzzle into Loopy save file, including the state of the edges.
+pub fn to_save(puzzle: &Puzzle) -> EncodeLoopyResult<String> {
+    try!(check_encodable(puzzle));
+
+    let params = params(puzzle);
+    let mut records = vec![("SAVEFILE", SAVE_HEADER.to_owned()),
+                           ("VERSION", "1".to_owned()),
+                           ("GAME", GAME_NAME.to_owned()),
+                           ("PARAMS", params.clone()),
+                           ("CPARAMS", params),
+                           ("DESC", desc(puzzle))];
+
+    let mv = edges(puzzle)
+                 .into_iter()
+                 .enumerate()
+                 .filter_map(|(i, e)| {
+                     match get_edge(puzzle, e) {
+                         Some(Edge::Line) => Some(format!("{}y", i)),
+                         Some(Edge::Cross) => Some(format!("{}n", i)),
+                         None => None,
+                     }
+                 })
+                 .collect::<String>();
+    if mv.is_empty() {
+        records.push(("NSTATES", "1".to_owned()));
+        records.push(("STATEPOS", "1".to_owned()));
+    } else {
+        records.push(("NSTATES", "2".to_owned()));
+        records.push(("STATEPOS", "2".to_owned()));
+        records.push(("MOVE", mv));
+    }
+
+    Ok(records.into_iter()
+              .map(|(k, v)| format!("{:8}:{}:{}\n", k, v.len(), v))
+              .collect())
+}
+
+// Loopy has the exact clues of the faces of the whole rectangle only.
+fn check_encodable(puzzle: &Puzzle) -> EncodeLoopyResult<()> {
+    if puzzle.points().any(|p| puzzle.is_masked(p)) {
+        return Err(EncodeLoopyError::masked());
+    }
+    for r in 0..(puzzle.row() + 1) {
+        for c in 0..(puzzle.column() + 1) {
+            if puzzle.vertex_hint(Point(r, c)).is_some() {
+                return Err(EncodeLoopyError::vertex_hint());
+            }
+        }
+    }
+    if puzzle.points().any(|p| puzzle.marker(p).is_some()) {
+        return Err(EncodeLoopyError::marker());
+    }
+    if puzzle.points().any(|p| puzzle.hint_set(p).is_some()) {
+        return Err(EncodeLoopyError::hint_set());
+    }
+    if puzzle.loop_length().is_some() || puzzle.area().is_some() {
+        return Err(EncodeLoopyError::constraint());
+    }
+    Ok(())
+}
+
+fn params(puzzle: &Puzzle) -> String {
+    format!("{}x{}t0", puzzle.column(), puzzle.row())
+}
+
+// `<width>x<height>[t<type>][d<difficulty>]`
+fn parse_params(s: &str) -> ParseLoopyResult<Size> {
+    use self::ParseLoopyError as Error;
+
+    fn number(s: &str) -> (Option<i32>, &str) {
+        let len = s.find(|c: char| !c.is_digit(10)).unwrap_or(s.len());
+        (s[..len].parse().ok(), &s[len..])
+    }
+
+    let (w, s) = number(s);
+    if !s.starts_with('x') {
+        return Err(Error::invalid_params());
+    }
+    let (h, mut s) = number(&s[1..]);
+    let (w, h) = match (w, h) {
+        (Some(w), Some(h)) if w > 0 && h > 0 => (w, h),
+        _ => return Err(Error::invalid_params()),
+    };
+
+    if s.starts_with('t') {
+        let (ty, rest) = number(&s[1..]);
+        match ty {
+            Some(0) => {}
+            Some(_) => return Err(Error::unsupported_grid()),
+            None => return Err(Error::invalid_params()),
+        }
+        s = rest;
+    }
+    if s.starts_with('d') && s.len() == 2 {
+        s = &s[2..];
+    }
+    if !s.is_empty() {
+        return Err(Error::invalid_params());
+    }
+
+    Ok(Size(h, w))
+}
+
+fn parse_desc(size: Size, desc: &str) -> ParseLoopyResult<Puzzle> {
+    use self::ParseLoopyError as Error;
+
+    let len = (size.0 * size.1) as usize;
+    let mut puzzle = Puzzle::new(size);
+    let mut i = 0;
+    for c in desc.chars() {
+        match c {
+            '0'...'4' if i < len => {
+                let p = Point((i / size.1 as usize) as i32, (i % size.1 as usize) as i32);
+                puzzle.set_hint(p, Some(c as u8 - b'0'));
+                i += 1;
+            }
+            'a'...'z' => i += (c as u8 - b'a') as usize + 1,
+            _ => return Err(Error::invalid_desc()),
+        }
+    }
+    if i != len {
+        return Err(Error::invalid_desc());
+    }
+
+    Ok(puzzle)
+}
+
+fn desc(puzzle: &Puzzle) -> String {
+    let mut s = String::new();
+    let mut count = 0;
+    for p in puzzle.points() {
+        match puzzle.hint(p) {
+            Some(n) => {
+                if count > 0 {
+                    s.push((b'a' + count - 1) as char);
+                    count = 0;
+                }
+                s.push((b'0' + n) as char);
+            }
+            None => {
+                count += 1;
+                if count == 26 {
+                    s.push('z');
+                    count = 0;
+                }
+            }
+        }
+    }
+    if count > 0 {
+        s.push((b'a' + count - 1) as char);
+    }
+    s
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum EdgeId {
+    H(Point),
+    V(Point),
+}
+
+// Loopy numbers the edges in the order it visits them going around each face
+// (top, right, bottom, left) in row-major order.
+fn edges(puzzle: &Puzzle) -> Vec<EdgeId> {
+    let mut edges = vec![];
+    for p in puzzle.points() {
+        let sides = [EdgeId::H(p),
+                     EdgeId::V(p + Move::RIGHT),
+                     EdgeId::H(p + Move::DOWN),
+                     EdgeId::V(p)];
+        for &e in &sides {
+            if !edges.contains(&e) {
+                edges.push(e);
+            }
+        }
+    }
+    edges
+}
+
+fn get_edge(puzzle: &Puzzle, e: EdgeId) -> Option<Edge> {
+    match e {
+        EdgeId::H(p) => puzzle.edge_h(p),
+        EdgeId::V(p) => puzzle.edge_v(p),
+    }
+}
+
+fn set_edge(puzzle: &mut Puzzle, e: EdgeId, edge: Option<Edge>) {
+    match e {
+        EdgeId::H(p) => puzzle.set_edge_h(p, edge),
+        EdgeId::V(p) => puzzle.set_edge_v(p, edge),
+    }
+}
+
+// `[S]<edge><y|n|u>...`
+fn apply_move(puzzle: &mut Puzzle, mv: &str) -> ParseLoopyResult<()> {
+    use self::ParseLoopyError as Error;
+
+    let edges = edges(puzzle);
+    let mut mv = if mv.starts_with('S') {
+        &mv[1..]
+    } else {
+        mv
+    };
+    while !mv.is_empty() {
+        let len = mv.find(|c: char| !c.is_digit(10)).unwrap_or(mv.len());
+        let e = match mv[..len].parse::<usize>().ok().and_then(|i| edges.get(i)) {
+            Some(&e) => e,
+            None => return Err(Error::invalid_move()),
+        };
+        let edge = match mv[len..].chars().next() {
+            Some('y') => Some(Edge::Line),
+            Some('n') => Some(Edge::Cross),
+            Some('u') => None,
+            _ => return Err(Error::invalid_move()),
+        };
+        set_edge(puzzle, e, edge);
+        mv = &mv[len + 1..];
+    }
+    Ok(())
+}
+
+// `<key>:<length>:<value>` records.
+fn parse_records(mut s: &str) -> ParseLoopyResult<Vec<(&str, &str)>> {
+    use self::ParseLoopyError as Error;
+
+    let mut records = vec![];
+    while !s.trim().is_empty() {
+        let mut it = s.splitn(3, ':');
+        let (key, len, rest) = match (it.next(), it.next(), it.next()) {
+            (Some(k), Some(l), Some(r)) => (k.trim(), l.parse::<usize>(), r),
+            _ => return Err(Error::invalid_record()),
+        };
+        let len = match len {
+            Ok(len) if len <= rest.len() && rest.is_char_boundary(len) => len,
+            _ => return Err(Error::invalid_record()),
+        };
+        records.push((key, &rest[..len]));
+        s = rest[len..].trim_left_matches(|c| c == '\r' || c == '\n');
+    }
+
+    match records.first() {
+        Some(&("SAVEFILE", SAVE_HEADER)) => Ok(records),
+        _ => Err(Error::invalid_record()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+    use super::{EncodeLoopyError, ParseLoopyError, ParseLoopyResult};
+    use geom::{Geom, Point, Size};
+    use puzzle::{Edge, HintSet, Marker, Puzzle};
+
+    fn check_error<T>(result: ParseLoopyResult<T>, error: ParseLoopyError) {
+        match result {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => assert_eq!(e.description(), error.description()),
+        }
+    }
+
+    #[test]
+    fn parse_game_id() {
+        let puzzle = super::parse_game_id("4x2t0de:3b0a21a").unwrap();
+        assert_eq!(Size(2, 4), puzzle.size());
+        assert_eq!(Some(3), puzzle.hint(Point(0, 0)));
+        assert_eq!(None, puzzle.hint(Point(0, 1)));
+        assert_eq!(None, puzzle.hint(Point(0, 2)));
+        assert_eq!(Some(0), puzzle.hint(Point(0, 3)));
+        assert_eq!(None, puzzle.hint(Point(1, 0)));
+        assert_eq!(Some(2), puzzle.hint(Point(1, 1)));
+        assert_eq!(Some(1), puzzle.hint(Point(1, 2)));
+        assert_eq!(None, puzzle.hint(Point(1, 3)));
+
+        assert!(super::is_game_id("4x2:3b0a21a"));
+        assert_eq!(puzzle, super::parse_game_id("4x2:3b0a21a").unwrap());
+        assert_eq!("4x2t0:3b0a21a", super::to_game_id(&puzzle).unwrap());
+
+        let empty = Puzzle::new(Size(6, 5));
+        assert_eq!("5x6t0:zd", super::to_game_id(&empty).unwrap());
+        assert_eq!(empty, super::parse_game_id("5x6t0:zd").unwrap());
+    }
+
+    #[test]
+    fn save_roundtrip() {
+        let mut puzzle = super::parse_game_id("2x1t0:3a").unwrap();
+        puzzle.set_edge_h(Point(0, 0), Some(Edge::Line));
+        puzzle.set_edge_v(Point(0, 1), Some(Edge::Cross));
+        puzzle.set_edge_v(Point(0, 0), Some(Edge::Line));
+
+        let save = super::to_save(&puzzle).unwrap();
+        assert_eq!("SAVEFILE:41:Simon Tatham's Portable Puzzle Collection
+VERSION :1:1
+GAME    :5:Loopy
+PARAMS  :5:2x1t0
+CPARAMS :5:2x1t0
+DESC    :2:3a
+NSTATES :1:2
+STATEPOS:1:2
+MOVE    :6:0y1n3y
+",
+                   save);
+        assert!(super::is_save(&save));
+        assert_eq!(puzzle, super::parse_save(&save).unwrap());
+    }
+
+    #[test]
+    fn encode_invalid() {
+        let mut puzzle = super::parse_game_id("2x1t0:3a").unwrap();
+        puzzle.set_vertex_hint(Point(0, 1), Some(2));
+        assert_eq!(EncodeLoopyError::vertex_hint().description(),
+                   super::to_game_id(&puzzle).unwrap_err().description());
+        assert_eq!(EncodeLoopyError::vertex_hint().description(),
+                   super::to_save(&puzzle).unwrap_err().description());
+
+        let mut puzzle = super::parse_game_id("2x1t0:3a").unwrap();
+        puzzle.set_marker(Point(0, 1), Some(Marker::Wolf));
+        assert_eq!(EncodeLoopyError::marker().description(),
+                   super::to_game_id(&puzzle).unwrap_err().description());
+
+        let mut puzzle = super::parse_game_id("2x1t0:3a").unwrap();
+        puzzle.set_hint_set(Point(0, 1), Some(HintSet::any()));
+        assert_eq!(EncodeLoopyError::hint_set().description(),
+                   super::to_save(&puzzle).unwrap_err().description());
+
+        let mut puzzle = super::parse_game_id("2x1t0:3a").unwrap();
+        puzzle.set_masked(Point(0, 1), true);
+        assert_eq!(EncodeLoopyError::masked().description(),
+                   super::to_game_id(&puzzle).unwrap_err().description());
+        assert_eq!(EncodeLoopyError::masked().description(),
+                   super::to_save(&puzzle).unwrap_err().description());
+
+        let mut puzzle = super::parse_game_id("2x1t0:3a").unwrap();
+        puzzle.set_loop_length(Some(4));
+        assert_eq!(EncodeLoopyError::constraint().description(),
+                   super::to_save(&puzzle).unwrap_err().description());
+        let mut puzzle = super::parse_game_id("2x1t0:3a").unwrap();
+        puzzle.set_area(Some(1));
+        assert_eq!(EncodeLoopyError::constraint().description(),
+                   super::to_game_id(&puzzle).unwrap_err().description());
+    }
+
+    #[test]
+    fn parse_save() {
+        let save = "SAVEFILE:41:Simon Tatham's Portable Puzzle Collection
+VERSION :1:1
+GAME    :5:Loopy
+PARAMS  :7:2x1t0dn
+CPARAMS :7:2x1t0dn
+DESC    :2:3a
+NSTATES :1:5
+STATEPOS:1:4
+MOVE    :2:4y
+MOVE    :4:5y6n
+MOVE    :2:4u
+MOVE    :2:2y
+";
+        let puzzle = super::parse_save(save).unwrap();
+        assert_eq!(Some(3), puzzle.hint(Point(0, 0)));
+        assert_eq!(None, puzzle.edge_h(Point(0, 1)));
+        assert_eq!(Some(Edge::Line), puzzle.edge_v(Point(0, 2)));
+        assert_eq!(Some(Edge::Cross), puzzle.edge_h(Point(1, 1)));
+        assert_eq!(None, puzzle.edge_h(Point(1, 0)));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        check_error(super::parse_game_id("4x:"), ParseLoopyError::invalid_params());
+        check_error(super::parse_game_id("4x2t1:h"),
+                    ParseLoopyError::unsupported_grid());
+        check_error(super::parse_game_id("4x2:g"), ParseLoopyError::invalid_desc());
+        check_error(super::parse_game_id("4x2:i"), ParseLoopyError::invalid_desc());
+        check_error(super::parse_game_id("4x2:5g"), ParseLoopyError::invalid_desc());
+        check_error(super::parse_save("SAVEFILE:41:Simon Tatham's Portable Puzzle \
+                                       Collection\nGAME    :3:Net\n"),
+                    ParseLoopyError::unsupported_game());
+        check_error(super::parse_save("SAVEFILE:99:x\n"),
+                    ParseLoopyError::invalid_record());
+    }
+}
@@ -7,16 +7,152 @@
 // modified, or distributed except according to those terms.
 
 //! Slither link puzzle data structure.
+//!
+//! # Text representation
+//!
+//! A puzzle is written either as rows of hints (`0`-`4`, or `_` and `-` for
+//! no hint), or as a lattice of `+` whose edges are `|` or `-` for lines and
+//! `x` for crosses. In both of them, `#` is a masked cell, which is not a
+//! part of the board, `s` and `w` are a sheep and a wolf, and `?` is a hint
+//! whose number is not given. In the lattice, a cell may have a set of the
+//! numbers which its hint may be, written as the digits in a row (`12` for
+//! "1 or 2"), each cell may have a marker `i` (inside) or `o` (outside) next
+//! to its hint, and a vertex may have a hint `0`-`4` in place of `+`, which
+//! is the number of the lines at the vertex. Each row and column of the
+//! lattice needs at least one `+`. `Display` writes the lattice, with two
+//! characters wide cells if any cell has its side.
+//!
+//! ```text
+//! +--2--+xx+
+//! |3i x o  x
+//! +--+xx+xx+
+//! ```
+//!
+//! Edges and cells may be several characters wide and cells may be several
+//! lines high, and box-drawing characters are read as `+`, `-` and `|`. The
+//! layout of `srither solve --output-mode pretty-ascii`, with the labels of
+//! the rows and the columns, is also accepted. There, blank edges are
+//! crosses, `~` and `?` are undecided edges and `#` is a masked cell.
+//!
+//! The puzzle may start with the lines `length: <number>` and `area:
+//! <number>`, which give the number of the lines of the loop and the number
+//! of the cells inside of the loop.
+//!
+//! ```text
+//! length: 6
+//! 3__
+//! ```
+//!
+//! # JSON representation
+//!
+//! With the `serde` feature, `Puzzle` implements `Serialize` as an object
+//! with the following fields. All tables are arrays of rows.
+//!
+//! * `size`: `{ "rows": <number>, "columns": <number> }`.
+//! * `loop_length`, `area`: the length of the loop and the number of the
+//!   cells inside of it, or `null` if not given.
+//! * `hints`: `rows` x `columns` table of `0`-`4`, or `null` for no hint.
+//! * `sides`: `rows` x `columns` table of `"in"`, `"out"` or `null`.
+//! * `masked`: `rows` x `columns` table of `true` for the masked cells and
+//!   `false` for the others.
+//! * `hint_sets`: `rows` x `columns` table of the arrays of the numbers
+//!   which the hints may be (`[0, 1, 2, 3, 4]` for `?`), or `null` for the
+//!   cells without them.
+//! * `markers`: `rows` x `columns` table of `"sheep"`, `"wolf"` or `null`.
+//! * `vertex_hints`: `rows + 1` x `columns + 1` table of the hints of the
+//!   vertices, where `vertex_hints[r][c]` is the top-left corner of the cell
+//!   `(r, c)`.
+//! * `edges_h`: `rows + 1` x `columns` table of the horizontal edges, where
+//!   `edges_h[r][c]` is the edge above the cell `(r, c)`. Each edge is
+//!   `"line"`, `"cross"` or `null`.
+//! * `edges_v`: `rows` x `columns + 1` table of the vertical edges, where
+//!   `edges_v[r][c]` is the edge on the left of the cell `(r, c)`.
+//!
+//! ```json
+//! {
+//!   "size": { "rows": 1, "columns": 1 },
+//!   "loop_length": null,
+//!   "area": null,
+//!   "hints": [[4]],
+//!   "sides": [["in"]],
+//!   "masked": [[false]],
+//!   "hint_sets": [[null]],
+//!   "markers": [[null]],
+//!   "vertex_hints": [[null, null], [null, null]],
+//!   "edges_h": [["line"], ["line"]],
+//!   "edges_v": [["line", "line"]]
+//! }
+//! ```
 
 use std::error::Error;
 use std::fmt;
 
-use geom::{Geom, Point, Size, Table};
+use diagnostic::Span;
+use geom::{Geom, Move, Point, Size, Table};
 use lattice_parser::ParseLatticeError;
 
 /// A hint of the slither link puzzle.
 pub type Hint = Option<u8>;
 
+/// A set of the numbers which a hint may be, for the cells whose hint is not
+/// given exactly.
+///
+/// Such cells have no `Hint`. `?` is the set of all the numbers from 0 to 4.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct HintSet(u8);
+
+impl HintSet {
+    /// Creates a set of the numbers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nums` is empty or a number is greater than 4.
+    pub fn new(nums: &[u8]) -> HintSet {
+        assert!(!nums.is_empty());
+        HintSet(nums.iter().fold(0, |bits, &n| {
+            assert!(n <= 4);
+            bits | (1 << n)
+        }))
+    }
+
+    /// Creates a set of all the numbers, which is written as `?`.
+    pub fn any() -> HintSet {
+        HintSet(0x1f)
+    }
+
+    /// Returns whether the hint may be the number.
+    pub fn contains(self, n: u8) -> bool {
+        n <= 4 && self.0 & (1 << n) != 0
+    }
+
+    /// Returns the numbers in the set in ascending order.
+    pub fn values(self) -> Vec<u8> {
+        (0..5).filter(|&n| self.contains(n)).collect()
+    }
+
+    /// Returns the smallest number in the set.
+    pub fn min(self) -> u8 {
+        self.0.trailing_zeros() as u8
+    }
+
+    /// Returns the largest number in the set.
+    pub fn max(self) -> u8 {
+        7 - self.0.leading_zeros() as u8
+    }
+}
+
+impl fmt::Display for HintSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if *self == HintSet::any() {
+            return write!(f, "?");
+        }
+        for n in self.values() {
+            try!(write!(f, "{}", n));
+        }
+        Ok(())
+    }
+}
+
 /// A cell's side.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub enum Side {
@@ -35,14 +171,42 @@ pub enum Edge {
     Cross,
 }
 
+/// A marker of the sheep and wolves variant.
+///
+/// All the sheep are on one side of the loop and all the wolves are on the
+/// other side, but which side is inside is not given.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Marker {
+    /// A sheep.
+    Sheep,
+    /// A wolf.
+    Wolf,
+}
+
+/// How strictly a string is parsed into puzzle.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ParseMode {
+    /// Unknown edges are read as undecided, and text outside of the lattice
+    /// is ignored.
+    Lenient,
+    /// Unknown edges and text outside of the lattice are errors.
+    Strict,
+}
+
 /// Slither link puzzle data.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Puzzle {
     size: Size,
     hint: Table<Hint>,
     side: Table<Option<Side>>,
+    mask: Table<bool>,
+    hint_set: Table<Option<HintSet>>,
+    marker: Table<Option<Marker>>,
+    vertex_hint: Table<Hint>,
     edge_v: Table<Option<Edge>>,
     edge_h: Table<Option<Edge>>,
+    loop_length: Option<u32>,
+    area: Option<u32>,
     sum_of_hint: u32,
 }
 
@@ -74,14 +238,24 @@ impl Puzzle {
         }
         let hint = Table::new(size, None, hint);
         let side = Table::new(size, Some(Side::Out), side);
+        let mask = Table::new_empty(size, false, false);
+        let hint_set = Table::new_empty(size, None, None);
+        let marker = Table::new_empty(size, None, None);
+        let vertex_hint = Table::new_empty(Size(size.0 + 1, size.1 + 1), None, None);
         let edge_v = Table::new(Size(size.0, size.1 + 1), Some(Edge::Cross), edge_v);
         let edge_h = Table::new(Size(size.0 + 1, size.1), Some(Edge::Cross), edge_h);
         Puzzle {
             size: size,
             hint: hint,
             side: side,
+            mask: mask,
+            hint_set: hint_set,
+            marker: marker,
+            vertex_hint: vertex_hint,
             edge_v: edge_v,
             edge_h: edge_h,
+            loop_length: None,
+            area: None,
             sum_of_hint: sum_of_hint,
         }
     }
@@ -116,6 +290,115 @@ impl Puzzle {
         self.side[p] = side;
     }
 
+    /// Returns whether the cell at the point is masked.
+    ///
+    /// Masked cells are not part of the board. They have no hint and are
+    /// always outside of the loop.
+    #[inline]
+    pub fn is_masked(&self, p: Point) -> bool {
+        self.mask[p]
+    }
+
+    /// Sets whether the cell at the point is masked.
+    #[inline]
+    pub fn set_masked(&mut self, p: Point, masked: bool) {
+        self.mask[p] = masked;
+    }
+
+    /// Gets a set of the numbers which the hint at the point may be.
+    #[inline]
+    pub fn hint_set(&self, p: Point) -> Option<HintSet> {
+        self.hint_set[p]
+    }
+
+    /// Sets a set of the numbers which the hint at the point may be. The cell
+    /// should not have its `Hint` at the same time.
+    #[inline]
+    pub fn set_hint_set(&mut self, p: Point, set: Option<HintSet>) {
+        self.hint_set[p] = set;
+    }
+
+    /// Gets the number of the lines of the loop, if given.
+    #[inline]
+    pub fn loop_length(&self) -> Option<u32> {
+        self.loop_length
+    }
+
+    /// Sets the number of the lines of the loop.
+    #[inline]
+    pub fn set_loop_length(&mut self, length: Option<u32>) {
+        self.loop_length = length;
+    }
+
+    /// Gets the number of the cells inside of the loop, if given.
+    #[inline]
+    pub fn area(&self) -> Option<u32> {
+        self.area
+    }
+
+    /// Sets the number of the cells inside of the loop.
+    #[inline]
+    pub fn set_area(&mut self, area: Option<u32>) {
+        self.area = area;
+    }
+
+    /// Gets a marker at the point.
+    #[inline]
+    pub fn marker(&self, p: Point) -> Option<Marker> {
+        self.marker[p]
+    }
+
+    /// Sets a marker at the point.
+    #[inline]
+    pub fn set_marker(&mut self, p: Point, marker: Option<Marker>) {
+        self.marker[p] = marker;
+    }
+
+    /// Returns whether the cell at the point is in the puzzle and is not
+    /// masked.
+    #[inline]
+    pub fn is_on_board(&self, p: Point) -> bool {
+        self.contains(p) && !self.mask[p]
+    }
+
+    /// Returns whether the horizontal edge at the point touches any cell on
+    /// the board.
+    ///
+    /// The edges and the vertices which do not touch any cell on the board
+    /// are not drawn, so that the masked cells are left blank.
+    #[inline]
+    pub fn is_drawn_edge_h(&self, p: Point) -> bool {
+        self.is_on_board(p) || self.is_on_board(p + Move::UP)
+    }
+
+    /// Returns whether the vertical edge at the point touches any cell on the
+    /// board.
+    #[inline]
+    pub fn is_drawn_edge_v(&self, p: Point) -> bool {
+        self.is_on_board(p) || self.is_on_board(p + Move::LEFT)
+    }
+
+    /// Returns whether the vertex at the point touches any cell on the board.
+    #[inline]
+    pub fn is_drawn_vertex(&self, p: Point) -> bool {
+        self.is_drawn_edge_h(p) || self.is_drawn_edge_h(p + Move::LEFT)
+    }
+
+    /// Gets a hint at the vertex, which is the top-left corner of the cell at
+    /// the same point.
+    ///
+    /// The hint is the number of the lines at the vertex.
+    #[inline]
+    pub fn vertex_hint(&self, p: Point) -> Hint {
+        self.vertex_hint[p]
+    }
+
+    /// Sets a hint at the vertex.
+    #[inline]
+    pub fn set_vertex_hint(&mut self, p: Point, hint: Hint) {
+        self.vertex_hint[p] = hint;
+    }
+
     /// Gets a horizontal edge above the point.
     #[inline]
     pub fn edge_h(&self, p: Point) -> Option<Edge> {
@@ -152,6 +435,7 @@ impl Geom for Puzzle {
 #[derive(Copy, Clone, Debug)]
 pub struct ParsePuzzleError {
     kind: PuzzleErrorKind,
+    span: Option<Span>,
 }
 
 /// Puzzle parse result.
@@ -164,12 +448,21 @@ enum PuzzleErrorKind {
     TooSmallColumns,
     LengthMismatch,
     InvalidHint,
+    InvalidEdge,
+    TextOutsideLattice,
+    InvalidLayout,
+    NoNfpQuestion,
+    InvalidNfpEncoding,
+    InvalidConstraint,
     Lattice(ParseLatticeError),
 }
 
 impl From<ParseLatticeError> for ParsePuzzleError {
     fn from(err: ParseLatticeError) -> ParsePuzzleError {
-        ParsePuzzleError { kind: PuzzleErrorKind::Lattice(err) }
+        ParsePuzzleError {
+            kind: PuzzleErrorKind::Lattice(err),
+            span: err.span(),
+        }
     }
 }
 
@@ -182,13 +475,21 @@ impl Error for ParsePuzzleError {
             TooSmallColumns => "the number of columns is too small to parse puzzle",
             LengthMismatch => "the length of lines are not same",
             InvalidHint => "invalid hint found in string",
+            InvalidEdge => "invalid edge found in string",
+            TextOutsideLattice => "text found outside of lattice",
+            InvalidLayout => "invalid layout of pretty-printed puzzle",
+            NoNfpQuestion => "cannot found dataQuestion in nfp string",
+            InvalidNfpEncoding => "invalid percent-encoding found in nfp string",
+            InvalidConstraint => "invalid length or area of loop found in string",
             Lattice(ref e) => e.description(),
         }
     }
     fn cause(&self) -> Option<&Error> {
         use self::PuzzleErrorKind::*;
         match self.kind {
-            Empty | TooSmallRows | TooSmallColumns | LengthMismatch | InvalidHint => None,
+            Empty | TooSmallRows | TooSmallColumns | LengthMismatch | InvalidHint |
+            InvalidEdge | TextOutsideLattice | InvalidLayout | NoNfpQuestion |
+            InvalidNfpEncoding | InvalidConstraint => None,
             Lattice(ref e) => Some(e),
         }
     }
@@ -196,62 +497,393 @@ impl Error for ParsePuzzleError {
 
 impl fmt::Display for ParsePuzzleError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.description().fmt(f)
+        match self.span {
+            Some(span) => write!(f, "{} at {}", self.description(), span),
+            None => self.description().fmt(f),
+        }
     }
 }
 
 impl ParsePuzzleError {
     fn empty() -> ParsePuzzleError {
-        ParsePuzzleError { kind: PuzzleErrorKind::Empty }
+        ParsePuzzleError {
+            kind: PuzzleErrorKind::Empty,
+            span: None,
+        }
     }
     fn too_small_rows() -> ParsePuzzleError {
-        ParsePuzzleError { kind: PuzzleErrorKind::TooSmallRows }
+        ParsePuzzleError {
+            kind: PuzzleErrorKind::TooSmallRows,
+            span: None,
+        }
     }
     fn too_small_columns() -> ParsePuzzleError {
-        ParsePuzzleError { kind: PuzzleErrorKind::TooSmallColumns }
+        ParsePuzzleError {
+            kind: PuzzleErrorKind::TooSmallColumns,
+            span: None,
+        }
     }
     fn length_mismatch() -> ParsePuzzleError {
-        ParsePuzzleError { kind: PuzzleErrorKind::LengthMismatch }
+        ParsePuzzleError {
+            kind: PuzzleErrorKind::LengthMismatch,
+            span: None,
+        }
     }
     fn invalid_hint() -> ParsePuzzleError {
-        ParsePuzzleError { kind: PuzzleErrorKind::InvalidHint }
+        ParsePuzzleError {
+            kind: PuzzleErrorKind::InvalidHint,
+            span: None,
+        }
+    }
+    fn invalid_edge() -> ParsePuzzleError {
+        ParsePuzzleError {
+            kind: PuzzleErrorKind::InvalidEdge,
+            span: None,
+        }
+    }
+    fn text_outside_lattice() -> ParsePuzzleError {
+        ParsePuzzleError {
+            kind: PuzzleErrorKind::TextOutsideLattice,
+            span: None,
+        }
+    }
+    fn invalid_layout() -> ParsePuzzleError {
+        ParsePuzzleError {
+            kind: PuzzleErrorKind::InvalidLayout,
+            span: None,
+        }
+    }
+    fn no_nfp_question() -> ParsePuzzleError {
+        ParsePuzzleError {
+            kind: PuzzleErrorKind::NoNfpQuestion,
+            span: None,
+        }
+    }
+    fn invalid_nfp_encoding() -> ParsePuzzleError {
+        ParsePuzzleError {
+            kind: PuzzleErrorKind::InvalidNfpEncoding,
+            span: None,
+        }
+    }
+    fn invalid_constraint() -> ParsePuzzleError {
+        ParsePuzzleError {
+            kind: PuzzleErrorKind::InvalidConstraint,
+            span: None,
+        }
+    }
+
+    fn at(self, span: Span) -> ParsePuzzleError {
+        ParsePuzzleError { span: Some(span), ..self }
+    }
+    fn shift_lines(self, n: usize) -> ParsePuzzleError {
+        ParsePuzzleError { span: self.span.map(|s| s.shift_lines(n)), ..self }
+    }
+
+    /// Returns the position of the error in the parsed string, if known.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+}
+
+/// A rule of slither link broken by a solution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Violation {
+    /// The number of lines around the cell differs from its hint.
+    Hint {
+        /// The cell with the hint.
+        cell: Point,
+        /// The hint of the cell.
+        expected: u8,
+        /// The number of lines around the cell.
+        actual: u8,
+    },
+    /// The number of lines around the cell is not in its set of the numbers.
+    HintSet {
+        /// The cell with the set.
+        cell: Point,
+        /// The numbers which the hint may be.
+        expected: HintSet,
+        /// The number of lines around the cell.
+        actual: u8,
+    },
+    /// The number of lines at the vertex differs from its hint.
+    VertexHint {
+        /// The vertex with the hint, which is the top-left corner of the cell
+        /// at the same point.
+        vertex: Point,
+        /// The hint of the vertex.
+        expected: u8,
+        /// The number of lines at the vertex.
+        actual: u8,
+    },
+    /// The number of lines at the vertex is neither 0 nor 2.
+    Degree {
+        /// The vertex, which is the top-left corner of the cell at the same
+        /// point.
+        vertex: Point,
+        /// The number of lines at the vertex.
+        degree: u8,
+    },
+    /// The masked cell is inside of the loop.
+    Masked {
+        /// The masked cell.
+        cell: Point,
+    },
+    /// The markers are not on the right sides of the loop. Holds a pair of
+    /// the markers, which are both sheep or both wolves on the different
+    /// sides, or a sheep and a wolf on the same side.
+    Markers(Point, Point),
+    /// The number of the lines differs from the given length of the loop.
+    LoopLength {
+        /// The given length.
+        expected: u32,
+        /// The number of the lines.
+        actual: u32,
+    },
+    /// The number of the cells inside of the loop differs from the given
+    /// area.
+    Area {
+        /// The given area.
+        expected: u32,
+        /// The number of the cells inside of the loop.
+        actual: u32,
+    },
+    /// No line is drawn.
+    NoLoop,
+    /// The lines form several loops. Holds the top-left vertex of each loop.
+    MultipleLoops(Vec<Point>),
+}
+
+/// An error type which is returned from extracting the loop of a puzzle.
+#[derive(Copy, Clone, Debug)]
+pub struct LoopPathError {
+    kind: LoopPathErrorKind,
+    vertex: Option<Point>,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum LoopPathErrorKind {
+    NoLoop,
+    InvalidDegree,
+    MultipleLoops,
+}
+
+impl Error for LoopPathError {
+    fn description(&self) -> &str {
+        use self::LoopPathErrorKind::*;
+        match self.kind {
+            NoLoop => "no loop found in puzzle",
+            InvalidDegree => "the number of lines at a vertex is neither 0 nor 2",
+            MultipleLoops => "several loops found in puzzle",
+        }
+    }
+}
+
+impl fmt::Display for LoopPathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.vertex {
+            Some(p) => write!(f, "{} at vertex ({}, {})", self.description(), p.0, p.1),
+            None => self.description().fmt(f),
+        }
+    }
+}
+
+impl LoopPathError {
+    fn no_loop() -> LoopPathError {
+        LoopPathError {
+            kind: LoopPathErrorKind::NoLoop,
+            vertex: None,
+        }
+    }
+    fn invalid_degree(p: Point) -> LoopPathError {
+        LoopPathError {
+            kind: LoopPathErrorKind::InvalidDegree,
+            vertex: Some(p),
+        }
+    }
+    fn multiple_loops() -> LoopPathError {
+        LoopPathError {
+            kind: LoopPathErrorKind::MultipleLoops,
+            vertex: None,
+        }
+    }
+
+    /// Returns the vertex where the error is found, if any.
+    pub fn vertex(&self) -> Option<Point> {
+        self.vertex
     }
 }
 
 mod from_str_impl {
-    use super::{Edge, ParsePuzzleError as Error, Puzzle};
+    use super::{Edge, Hint, HintSet, Marker, ParseMode, ParsePuzzleError as Error, Puzzle,
+                Side};
     use std::str::FromStr;
-    use geom::Size;
+    use diagnostic::Span;
+    use geom::{Point, Size};
     use lattice_parser::LatticeParser;
 
     impl FromStr for Puzzle {
         type Err = Error;
 
         fn from_str(s: &str) -> Result<Puzzle, Error> {
-            let mut mat = s.lines()
-                           .map(|l| l.trim_matches('\n'))
-                           .map(|l| l.chars().collect::<Vec<_>>())
-                           .skip_while(|l| l.is_empty())
-                           .collect::<Vec<_>>();
-
-            // Drop trailing empty lines
-            while mat.last().map(|l| l.len()) == Some(0) {
-                let _ = mat.pop();
-            }
+            Puzzle::parse_with_mode(s, ParseMode::Lenient)
+        }
+    }
 
-            if mat.is_empty() {
-                return Err(Error::empty());
-            }
+    impl Puzzle {
+        /// Parses a string into puzzle in the given mode.
+        ///
+        /// `s.parse::<Puzzle>()` is the same as parsing in the lenient mode.
+        pub fn parse_with_mode(s: &str, mode: ParseMode) -> Result<Puzzle, Error> {
+            let lines = s.lines().collect::<Vec<_>>();
+            let (length, area, header) = try!(parse_header(&lines));
+            let s = lines[header..].join("\n");
 
-            if mat[0].iter().any(|&c| c == '+') {
-                parse_pat1(mat)
+            let mat = try!(to_matrix(&s).map_err(|e| e.shift_lines(header)));
+            // `to_matrix` drops the leading empty lines.
+            let skipped = header + s.lines().take_while(|l| l.is_empty()).count();
+            let result = if mat[0].iter().any(|&c| c == '+') {
+                parse_pat1(mat, mode)
+            } else if is_pretty(&mat) {
+                parse_pretty(mat, mode)
+            } else if mat.iter().any(|l| l.contains(&'+')) {
+                // The first line of the lattice has the hints on all the
+                // vertices.
+                parse_pat1(mat, mode)
             } else {
-                parse_pat2(mat)
+                // All the lines of the lattice may have the hints on all the
+                // vertices, which cannot be the hints of the cells.
+                parse_pat2(mat.clone()).or_else(|e| parse_pat1(mat, mode).map_err(|_| e))
+            };
+            let mut puzzle = try!(result.map_err(|e| e.shift_lines(skipped)));
+            puzzle.set_loop_length(length);
+            puzzle.set_area(area);
+            Ok(puzzle)
+        }
+    }
+
+    // Reads the `length` and `area` lines before the board. Returns them and
+    // the number of the lines read.
+    fn parse_header(lines: &[&str]) -> Result<(Option<u32>, Option<u32>, usize), Error> {
+        let (mut length, mut area, mut header) = (None, None, 0);
+        for (i, line) in lines.iter().enumerate() {
+            let mut it = line.splitn(2, ':');
+            let (key, value) = (it.next().unwrap_or("").trim(), it.next());
+            let target = match (key, value) {
+                ("length", Some(_)) => &mut length,
+                ("area", Some(_)) => &mut area,
+                _ if line.trim().is_empty() => continue,
+                _ => break,
+            };
+            match value.unwrap().trim().parse::<u32>() {
+                Ok(n) => *target = Some(n),
+                Err(_) => {
+                    let span = Span::new(i, 0, line.chars().count());
+                    return Err(Error::invalid_constraint().at(span));
+                }
             }
+            header = i + 1;
+        }
+        Ok((length, area, header))
+    }
+
+    // Box-drawing characters are read as their ASCII counterparts.
+    fn from_box_drawing(c: char) -> char {
+        match c {
+            '\u{2500}' | '\u{2501}' | '\u{2550}' => '-',
+            '\u{2502}' | '\u{2503}' | '\u{2551}' => '|',
+            '\u{250c}'...'\u{254b}' | '\u{2552}'...'\u{256c}' => '+',
+            _ => c,
+        }
+    }
+
+    pub fn to_matrix(s: &str) -> Result<Vec<Vec<char>>, Error> {
+        let mut mat = s.lines()
+                       .map(|l| l.trim_matches('\n'))
+                       .map(|l| l.chars().map(from_box_drawing).collect::<Vec<_>>())
+                       .skip_while(|l| l.is_empty())
+                       .collect::<Vec<_>>();
+
+        // Drop trailing empty lines
+        while mat.last().map(|l| l.len()) == Some(0) {
+            let _ = mat.pop();
         }
+
+        if mat.is_empty() {
+            return Err(Error::empty());
+        }
+
+        Ok(mat)
+    }
+
+    // Returns `None` if the text is neither blank nor an edge.
+    fn parse_edge(s: &str, line: char) -> Option<Option<Edge>> {
+        if s.chars().all(|c| c == ' ') {
+            Some(None)
+        } else if s.chars().all(|c| c == 'x') {
+            Some(Some(Edge::Cross))
+        } else if s.chars().all(|c| c == line) {
+            Some(Some(Edge::Line))
+        } else {
+            None
+        }
+    }
+
+    // The contents of a cell except for its side.
+    enum Content {
+        Hint(Hint),
+        HintSet(HintSet),
+        Masked,
+        Marker(Marker),
+    }
+
+    // `?`, or two or more digits for the numbers which the hint may be.
+    fn parse_hint_set(s: &str) -> Option<HintSet> {
+        if s == "?" {
+            return Some(HintSet::any());
+        }
+        if s.len() < 2 || !s.chars().all(|c| c >= '0' && c <= '4') {
+            return None;
+        }
+        let nums = s.bytes().map(|b| b - b'0').collect::<Vec<_>>();
+        Some(HintSet::new(&nums))
+    }
+
+    // The side marker is written before or after the contents.
+    fn parse_cell(s: &str) -> Option<(Content, Option<Side>)> {
+        let s = s.trim_matches(' ');
+        let (s, side) = if s.starts_with('i') {
+            (&s[1..], Some(Side::In))
+        } else if s.starts_with('o') {
+            (&s[1..], Some(Side::Out))
+        } else if s.ends_with('i') {
+            (&s[..s.len() - 1], Some(Side::In))
+        } else if s.ends_with('o') {
+            (&s[..s.len() - 1], Some(Side::Out))
+        } else {
+            (s, None)
+        };
+        let content = match s.trim_matches(' ') {
+            "0" => Content::Hint(Some(0)),
+            "1" => Content::Hint(Some(1)),
+            "2" => Content::Hint(Some(2)),
+            "3" => Content::Hint(Some(3)),
+            "4" => Content::Hint(Some(4)),
+            "" | "_" | "-" => Content::Hint(None),
+            "#" => Content::Masked,
+            "s" => Content::Marker(Marker::Sheep),
+            "w" => Content::Marker(Marker::Wolf),
+            s => {
+                match parse_hint_set(s) {
+                    Some(set) => Content::HintSet(set),
+                    None => return None,
+                }
+            }
+        };
+        Some((content, side))
     }
 
-    fn parse_pat1(mat: Vec<Vec<char>>) -> Result<Puzzle, Error> {
+    fn parse_pat1(mat: Vec<Vec<char>>, mode: ParseMode) -> Result<Puzzle, Error> {
         let parser = try!(LatticeParser::from_lines(&mat));
 
         let rows = parser.num_rows();
@@ -264,154 +896,998 @@ mod from_str_impl {
             return Err(Error::too_small_columns());
         }
 
-        let edge_v = parser.v_edges()
-                           .map(|(_, s)| {
-                               if s.is_empty() {
-                                   None
-                               } else if s.chars().all(|c| c == 'x') {
-                                   Some(Edge::Cross)
-                               } else if s.chars().all(|c| c == '|') {
-                                   Some(Edge::Line)
-                               } else {
-                                   None
-                               }
-                           })
-                           .collect();
-
-        let edge_h = parser.h_edges()
-                           .map(|(_, s)| {
-                               if s.is_empty() {
-                                   None
-                               } else if s.chars().all(|c| c == 'x') {
-                                   Some(Edge::Cross)
-                               } else if s.chars().all(|c| c == '-') {
-                                   Some(Edge::Line)
-                               } else {
-                                   None
-                               }
-                           })
-                           .collect();
-
-        let hint = parser.cells()
-                         .filter_map(|(_, s)| {
-                             match s.trim_matches(' ') {
-                                 "0" => Some(Some(0)),
-                                 "1" => Some(Some(1)),
-                                 "2" => Some(Some(2)),
-                                 "3" => Some(Some(3)),
-                                 "4" => Some(Some(4)),
-                                 "" | "_" | "-" => Some(None),
-                                 _ => None,
-                             }
-                         })
-                         .collect::<Vec<_>>();
-        if hint.len() != (rows - 1) * (cols - 1) {
-            return Err(Error::invalid_hint());
+        if mode == ParseMode::Strict {
+            if let Some(span) = parser.outside_text() {
+                return Err(Error::text_outside_lattice().at(span));
+            }
+        }
+
+        let mut edge_v = Vec::with_capacity((rows - 1) * cols);
+        for (p, s) in parser.v_edges() {
+            edge_v.push(match parse_edge(&s, '|') {
+                Some(edge) => edge,
+                None if mode == ParseMode::Strict => {
+                    return Err(Error::invalid_edge().at(parser.v_edge_span(p)))
+                }
+                None => None,
+            });
+        }
+
+        let mut edge_h = Vec::with_capacity(rows * (cols - 1));
+        for (p, s) in parser.h_edges() {
+            edge_h.push(match parse_edge(&s, '-') {
+                Some(edge) => edge,
+                None if mode == ParseMode::Strict => {
+                    return Err(Error::invalid_edge().at(parser.h_edge_span(p)))
+                }
+                None => None,
+            });
+        }
+
+        let mut hint = Vec::with_capacity((rows - 1) * (cols - 1));
+        let mut side = Vec::with_capacity((rows - 1) * (cols - 1));
+        let mut masked = vec![];
+        let mut sets = vec![];
+        let mut markers = vec![];
+        for (p, s) in parser.cells() {
+            let (content, sd) = match parse_cell(&s) {
+                Some(cell) => cell,
+                None => return Err(Error::invalid_hint().at(parser.cell_span(p))),
+            };
+            hint.push(match content {
+                Content::Hint(h) => h,
+                Content::HintSet(set) => {
+                    sets.push((p, set));
+                    None
+                }
+                Content::Masked => {
+                    masked.push(p);
+                    None
+                }
+                Content::Marker(m) => {
+                    markers.push((p, m));
+                    None
+                }
+            });
+            side.push(sd);
+        }
+
+        let mut vertex_hint = vec![];
+        for r in 0..rows {
+            for c in 0..cols {
+                let p = Point(r as i32, c as i32);
+                match parser.point(p) {
+                    '+' => {}
+                    ch @ '0'...'4' => vertex_hint.push((p, Some(ch as u8 - b'0'))),
+                    _ => return Err(Error::invalid_hint().at(parser.point_span(p))),
+                }
+            }
         }
 
         let size = Size((rows - 1) as i32, (cols - 1) as i32);
-        let side = vec![None; (rows - 1) * (cols - 1)];
-        Ok(Puzzle::with_data(size, hint, side, edge_v, edge_h))
+        let mut puzzle = Puzzle::with_data(size, hint, side, edge_v, edge_h);
+        for p in masked {
+            puzzle.set_masked(p, true);
+        }
+        for (p, set) in sets {
+            puzzle.set_hint_set(p, Some(set));
+        }
+        for (p, m) in markers {
+            puzzle.set_marker(p, Some(m));
+        }
+        for (p, h) in vertex_hint {
+            puzzle.set_vertex_hint(p, h);
+        }
+        Ok(puzzle)
+    }
+
+    fn is_label(line: &[char]) -> bool {
+        line.iter().all(|&c| c == ' ' || c.is_digit(10))
+    }
+
+    // The layout written by the pretty printer of the command line tool.
+    //
+    // The lattice is surrounded by the labels of the rows and the columns,
+    // and its lattice points are not always `+`. Each cell is `w` columns
+    // wide and `h` lines high, where `w` is the width of the labels on the
+    // left. Blank edges are crosses, and `~` and `?` are undecided edges.
+    // Digits at the lattice points are the hints of the vertices.
+    fn is_pretty(mat: &[Vec<char>]) -> bool {
+        let first = &mat[0];
+        first.first() == Some(&' ') && first.iter().any(|c| c.is_digit(10)) && is_label(first)
+    }
+
+    fn parse_pretty_edge(s: &[char], line: char) -> Option<Option<Edge>> {
+        if s.iter().all(|&c| c == ' ') {
+            Some(Some(Edge::Cross))
+        } else if s.iter().all(|&c| c == line) {
+            Some(Some(Edge::Line))
+        } else if s.iter().all(|&c| c == '~' || c == '?') {
+            Some(None)
+        } else {
+            None
+        }
+    }
+
+    fn parse_pretty(mat: Vec<Vec<char>>, mode: ParseMode) -> Result<Puzzle, Error> {
+        // Drops the labels of the columns.
+        let end = if mat.len() > 1 && is_label(&mat[mat.len() - 1]) {
+            mat.len() - 1
+        } else {
+            mat.len()
+        };
+        let grid = &mat[1..end];
+        if grid.is_empty() {
+            return Err(Error::too_small_rows());
+        }
+        // `grid[l]` is the line `l + 1` of the string.
+        let line_span = |l: usize| Span::new(l + 1, 0, grid[l].len());
+
+        // The labels of the columns are `w + 1` characters apart. With only
+        // one label, `w` is taken from the top-left lattice point instead,
+        // which is not written if the cells around it are masked.
+        let labels = (0..mat[0].len())
+                         .filter(|&i| mat[0][i] != ' ' && (i == 0 || mat[0][i - 1] == ' '))
+                         .collect::<Vec<_>>();
+        let (w, cols) = if labels.len() >= 2 {
+            (labels[1] - labels[0] - 1, labels.len())
+        } else {
+            let (w, len) = match grid[0].iter().position(|&c| c != ' ') {
+                Some(w) if w > 0 => (w, grid[0].len()),
+                _ => return Err(Error::invalid_layout().at(line_span(0))),
+            };
+            if len < w + 1 || (len - w - 1) % (w + 1) != 0 {
+                return Err(Error::invalid_layout().at(line_span(0)));
+            }
+            (w, (len - w - 1) / (w + 1))
+        };
+        if cols == 0 {
+            return Err(Error::too_small_columns());
+        }
+
+        let rows = grid.iter().filter(|l| l.iter().take(w).any(|&c| c != ' ')).count();
+        if rows == 0 {
+            return Err(Error::too_small_rows());
+        }
+        if (grid.len() - 1) % rows != 0 || (grid.len() - 1) / rows < 2 {
+            return Err(Error::invalid_layout().at(line_span(grid.len() - 1)));
+        }
+        let h = (grid.len() - 1) / rows - 1;
+
+        let chars = |l: usize, start: usize, n: usize| {
+            (start..start + n)
+                .map(|i| grid[l].get(i).cloned().unwrap_or(' '))
+                .collect::<Vec<_>>()
+        };
+        let edge = |s: &[char], line: char, span: Span| {
+            match parse_pretty_edge(s, line) {
+                Some(edge) => Ok(edge),
+                None if mode == ParseMode::Strict => Err(Error::invalid_edge().at(span)),
+                None => Ok(None),
+            }
+        };
+
+        let mut edge_h = Vec::with_capacity((rows + 1) * cols);
+        for r in 0..(rows + 1) {
+            let l = r * (h + 1);
+            for c in 0..cols {
+                let start = w + 1 + c * (w + 1);
+                let span = Span::new(l + 1, start, w);
+                edge_h.push(try!(edge(&chars(l, start, w), '-', span)));
+            }
+        }
+
+        let mut edge_v = Vec::with_capacity(rows * (cols + 1));
+        let mut hint = Vec::with_capacity(rows * cols);
+        let mut masked = vec![];
+        let mut sets = vec![];
+        let mut markers = vec![];
+        for r in 0..rows {
+            let ls = r * (h + 1) + 1;
+            for c in 0..(cols + 1) {
+                let start = w + c * (w + 1);
+                let s = (ls..ls + h).flat_map(|l| chars(l, start, 1)).collect::<Vec<_>>();
+                edge_v.push(try!(edge(&s, '|', Span::new(ls + 1, start, 1))));
+            }
+            for c in 0..cols {
+                let start = w + 1 + c * (w + 1);
+                let s = (ls..ls + h).flat_map(|l| chars(l, start, w)).collect::<String>();
+                let p = Point(r as i32, c as i32);
+                hint.push(match s.trim_matches(' ') {
+                    "0" => Some(0),
+                    "1" => Some(1),
+                    "2" => Some(2),
+                    "3" => Some(3),
+                    "4" => Some(4),
+                    "" => None,
+                    "#" => {
+                        masked.push(p);
+                        None
+                    }
+                    "s" => {
+                        markers.push((p, Marker::Sheep));
+                        None
+                    }
+                    "w" => {
+                        markers.push((p, Marker::Wolf));
+                        None
+                    }
+                    s => {
+                        match parse_hint_set(s) {
+                            Some(set) => sets.push((p, set)),
+                            None => {
+                                return Err(Error::invalid_hint().at(Span::new(ls + 1, start, w)))
+                            }
+                        }
+                        None
+                    }
+                });
+            }
+        }
+
+        let size = Size(rows as i32, cols as i32);
+        let side = vec![None; rows * cols];
+        let mut puzzle = Puzzle::with_data(size, hint, side, edge_v, edge_h);
+        for p in masked {
+            puzzle.set_masked(p, true);
+        }
+        for (p, set) in sets {
+            puzzle.set_hint_set(p, Some(set));
+        }
+        for (p, m) in markers {
+            puzzle.set_marker(p, Some(m));
+        }
+        for r in 0..(rows + 1) {
+            for c in 0..(cols + 1) {
+                if let ch @ '0'...'4' = chars(r * (h + 1), w + c * (w + 1), 1)[0] {
+                    puzzle.set_vertex_hint(Point(r as i32, c as i32), Some(ch as u8 - b'0'));
+                }
+            }
+        }
+        Ok(puzzle)
     }
 
-    fn parse_pat2(mat: Vec<Vec<char>>) -> Result<Puzzle, Error> {
+    pub fn parse_pat2(mat: Vec<Vec<char>>) -> Result<Puzzle, Error> {
         let row = mat.len();
         assert!(row > 0);
         let col = mat[0].len();
         assert!(col > 0);
-        if mat[1..].iter().any(|r| r.len() != col) {
-            return Err(Error::length_mismatch());
-        }
-
-        let hint = mat.iter()
-                      .flat_map(|line| {
-                          line.iter().filter_map(|&c| {
-                              match c {
-                                  '0' => Some(Some(0)),
-                                  '1' => Some(Some(1)),
-                                  '2' => Some(Some(2)),
-                                  '3' => Some(Some(3)),
-                                  '4' => Some(Some(4)),
-                                  '_' | '-' => Some(None),
-                                  _ => None,
-                              }
-                          })
-                      })
-                      .collect::<Vec<_>>();
-        if hint.len() != row * col {
-            return Err(Error::invalid_hint());
+        if let Some(r) = (1..row).find(|&r| mat[r].len() != col) {
+            return Err(Error::length_mismatch().at(Span::new(r, 0, mat[r].len())));
+        }
+
+        let mut hint = Vec::with_capacity(row * col);
+        let mut masked = vec![];
+        let mut sets = vec![];
+        let mut markers = vec![];
+        for (r, line) in mat.iter().enumerate() {
+            for (c, &ch) in line.iter().enumerate() {
+                let p = Point(r as i32, c as i32);
+                let h = match ch {
+                    '0' => Some(0),
+                    '1' => Some(1),
+                    '2' => Some(2),
+                    '3' => Some(3),
+                    '4' => Some(4),
+                    '_' | '-' => None,
+                    '#' => {
+                        masked.push(p);
+                        None
+                    }
+                    '?' => {
+                        sets.push((p, HintSet::any()));
+                        None
+                    }
+                    's' => {
+                        markers.push((p, Marker::Sheep));
+                        None
+                    }
+                    'w' => {
+                        markers.push((p, Marker::Wolf));
+                        None
+                    }
+                    _ => return Err(Error::invalid_hint().at(Span::new(r, c, 1))),
+                };
+                hint.push(h);
+            }
         }
 
         let size = Size(row as i32, col as i32);
         let side = vec![None; row * col];
         let edge_v = vec![None; row * (col + 1)];
         let edge_h = vec![None; (row + 1) * col];
-        Ok(Puzzle::with_data(size, hint, side, edge_v, edge_h))
+        let mut puzzle = Puzzle::with_data(size, hint, side, edge_v, edge_h);
+        for p in masked {
+            puzzle.set_masked(p, true);
+        }
+        for (p, set) in sets {
+            puzzle.set_hint_set(p, Some(set));
+        }
+        for (p, m) in markers {
+            puzzle.set_marker(p, Some(m));
+        }
+        Ok(puzzle)
     }
 }
 
-mod display_impl {
-    use super::{Edge, Puzzle};
-    use std::fmt;
-    use geom::{Geom, Point};
+mod nfp_impl {
+    use super::{ParsePuzzleError as Error, Puzzle};
+    use super::from_str_impl::{parse_pat2, to_matrix};
 
-    struct Cross;
-    impl fmt::Display for Cross {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "+")
+    impl Puzzle {
+        /// Parses a Nikoli `.nfp` file into puzzle.
+        ///
+        /// The hints are read from the percent-encoded `dataQuestion` value,
+        /// whose cells are separated by `+`.
+        pub fn from_nfp(s: &str) -> Result<Puzzle, Error> {
+            let question = s.split(|c| c == '&' || c == '\n')
+                            .map(|kv| kv.trim())
+                            .filter_map(|kv| {
+                                let mut it = kv.splitn(2, '=');
+                                match (it.next(), it.next()) {
+                                    (Some("dataQuestion"), Some(v)) => Some(v),
+                                    _ => None,
+                                }
+                            })
+                            .next();
+            let question = match question {
+                Some(q) => try!(percent_decode(q)),
+                None => return Err(Error::no_nfp_question()),
+            };
+
+            let question = question.chars().filter(|&c| c != '+').collect::<String>();
+            // The positions in the decoded question are meaningless to the caller.
+            parse_pat2(try!(to_matrix(&question))).map_err(|e| Error { span: None, ..e })
         }
     }
 
-    struct HEdge<'a>(&'a Puzzle, Point);
-    impl<'a> fmt::Display for HEdge<'a> {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            let HEdge(puzzle, p) = *self;
-            match puzzle.edge_h[p] {
-                Some(Edge::Cross) => try!(write!(f, "x")),
-                Some(Edge::Line) => try!(write!(f, "-")),
-                None => try!(write!(f, " ")),
+    fn percent_decode(s: &str) -> Result<String, Error> {
+        let mut bytes = vec![];
+        let mut it = s.bytes();
+        while let Some(b) = it.next() {
+            if b != b'%' {
+                bytes.push(b);
+                continue;
             }
-            Ok(())
+            let hex = match (it.next(), it.next()) {
+                (Some(h), Some(l)) => [h, l],
+                _ => return Err(Error::invalid_nfp_encoding()),
+            };
+            let hex = try!(::std::str::from_utf8(&hex).map_err(|_| Error::invalid_nfp_encoding()));
+            bytes.push(try!(u8::from_str_radix(hex, 16).map_err(|_| Error::invalid_nfp_encoding())));
         }
+        String::from_utf8(bytes).map_err(|_| Error::invalid_nfp_encoding())
     }
+}
 
-    struct VEdge<'a>(&'a Puzzle, Point);
-    impl<'a> fmt::Display for VEdge<'a> {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            let VEdge(puzzle, p) = *self;
-            match puzzle.edge_v[p] {
-                Some(Edge::Cross) => try!(write!(f, "x")),
-                Some(Edge::Line) => try!(write!(f, "|")),
-                None => try!(write!(f, " ")),
+// Returns the vertices connected to the vertex by lines. The vertex
+// `Point(r, c)` is the top-left corner of the cell `Point(r, c)`.
+fn line_neighbors(puzzle: &Puzzle, v: Point) -> Vec<Point> {
+    let edges = [(puzzle.edge_v(v + Move::UP), v + Move::UP),
+                 (puzzle.edge_h(v), v + Move::RIGHT),
+                 (puzzle.edge_v(v), v + Move::DOWN),
+                 (puzzle.edge_h(v + Move::LEFT), v + Move::LEFT)];
+    edges.iter()
+         .filter(|&&(e, _)| e == Some(Edge::Line))
+         .map(|&(_, p)| p)
+         .collect()
+}
+
+mod path_impl {
+    use super::{LoopPathError as Error, Puzzle, line_neighbors};
+    use geom::{Geom, Move, Point};
+
+    impl Puzzle {
+        /// Returns the vertices of the loop in order.
+        ///
+        /// The vertex `Point(r, c)` is the top-left corner of the cell
+        /// `Point(r, c)`, so the vertices range from `Point(0, 0)` to
+        /// `Point(row, column)`. The path starts from the top-left vertex of
+        /// the loop and goes clockwise. The first vertex is not repeated at
+        /// the end.
+        ///
+        /// Undecided edges are regarded as crosses.
+        pub fn loop_path(&self) -> Result<Vec<Point>, Error> {
+            let mut start = None;
+            let mut num_lines = 0;
+            for r in 0..(self.row() + 1) {
+                for c in 0..(self.column() + 1) {
+                    let v = Point(r, c);
+                    match line_neighbors(self, v).len() {
+                        0 => {}
+                        2 => {
+                            num_lines += 2;
+                            if start.is_none() {
+                                start = Some(v);
+                            }
+                        }
+                        _ => return Err(Error::invalid_degree(v)),
+                    }
+                }
             }
-            Ok(())
-        }
-    }
+            let start = match start {
+                Some(v) => v,
+                None => return Err(Error::no_loop()),
+            };
 
-    struct EdgeRow<'a>(&'a Puzzle, i32);
-    impl<'a> fmt::Display for EdgeRow<'a> {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            let EdgeRow(puzzle, r) = *self;
-            for c in 0..puzzle.column() {
-                let p = Point(r, c);
-                try!(write!(f, "{}", Cross));
-                try!(write!(f, "{}", HEdge(puzzle, p)));
+            // The top-left vertex has the lines on its right and below, and
+            // going right first makes the path clockwise.
+            let mut path = vec![start];
+            let (mut prev, mut cur) = (start, start + Move::RIGHT);
+            while cur != start {
+                path.push(cur);
+                let next = line_neighbors(self, cur)
+                               .into_iter()
+                               .find(|&p| p != prev)
+                               .unwrap();
+                prev = cur;
+                cur = next;
             }
-            try!(write!(f, "{}", Cross));
-            Ok(())
+
+            if path.len() != num_lines / 2 {
+                return Err(Error::multiple_loops());
+            }
+            Ok(path)
         }
     }
+}
+
+mod check_impl {
+    use std::collections::HashSet;
+    use super::{Edge, Marker, Puzzle, Violation, line_neighbors};
+    use geom::{Geom, Move, Point};
+
+    impl Puzzle {
+        /// Checks whether the lines of the puzzle are a solution.
+        ///
+        /// Returns all the broken rules, or an empty vector if the puzzle is
+        /// solved. Undecided edges are regarded as crosses. The masked cells,
+        /// the markers, the area and the number of the loops are checked only
+        /// if every vertex has 0 or 2 lines.
+        pub fn check_solution(&self) -> Vec<Violation> {
+            let mut violations = vec![];
+
+            for p in self.points() {
+                let edges = [self.edge_h(p),
+                             self.edge_h(p + Move::DOWN),
+                             self.edge_v(p),
+                             self.edge_v(p + Move::RIGHT)];
+                let actual = edges.iter().filter(|&&e| e == Some(Edge::Line)).count() as u8;
+                if let Some(expected) = self.hint(p) {
+                    if actual != expected {
+                        violations.push(Violation::Hint {
+                            cell: p,
+                            expected: expected,
+                            actual: actual,
+                        });
+                    }
+                }
+                if let Some(expected) = self.hint_set(p) {
+                    if !expected.contains(actual) {
+                        violations.push(Violation::HintSet {
+                            cell: p,
+                            expected: expected,
+                            actual: actual,
+                        });
+                    }
+                }
+            }
+
+            let vertices = (0..(self.row() + 1))
+                               .flat_map(|r| (0..(self.column() + 1)).map(move |c| Point(r, c)))
+                               .collect::<Vec<_>>();
+            for &v in &vertices {
+                if let Some(expected) = self.vertex_hint(v) {
+                    let actual = line_neighbors(self, v).len() as u8;
+                    if actual != expected {
+                        violations.push(Violation::VertexHint {
+                            vertex: v,
+                            expected: expected,
+                            actual: actual,
+                        });
+                    }
+                }
+            }
+
+            if let Some(expected) = self.loop_length {
+                let (rows, cols) = (self.row(), self.column());
+                let edges_h = (0..(rows + 1)).flat_map(|r| (0..cols).map(move |c| Point(r, c)));
+                let edges_v = (0..rows).flat_map(|r| (0..(cols + 1)).map(move |c| Point(r, c)));
+                let actual = edges_h.filter(|&p| self.edge_h(p) == Some(Edge::Line)).count() +
+                             edges_v.filter(|&p| self.edge_v(p) == Some(Edge::Line)).count();
+                if actual as u32 != expected {
+                    violations.push(Violation::LoopLength {
+                        expected: expected,
+                        actual: actual as u32,
+                    });
+                }
+            }
+
+            let mut valid_degree = true;
+            for &v in &vertices {
+                let degree = line_neighbors(self, v).len() as u8;
+                if degree != 0 && degree != 2 {
+                    valid_degree = false;
+                    violations.push(Violation::Degree {
+                        vertex: v,
+                        degree: degree,
+                    });
+                }
+            }
+            if !valid_degree {
+                return violations;
+            }
+
+            // A cell is inside if the lines on its left are odd.
+            let is_inside = |p: Point| {
+                let lines = (0..(p.1 + 1))
+                                .filter(|&c| self.edge_v(Point(p.0, c)) == Some(Edge::Line))
+                                .count();
+                lines % 2 == 1
+            };
+            for p in self.points().filter(|&p| self.is_masked(p)) {
+                if is_inside(p) {
+                    violations.push(Violation::Masked { cell: p });
+                }
+            }
+            if let Some(expected) = self.area {
+                let actual = self.points().filter(|&p| is_inside(p)).count() as u32;
+                if actual != expected {
+                    violations.push(Violation::Area {
+                        expected: expected,
+                        actual: actual,
+                    });
+                }
+            }
+
+            // The markers are compared with the first sheep and the first wolf.
+            let markers = self.points()
+                              .filter_map(|p| self.marker(p).map(|m| (p, m, is_inside(p))))
+                              .collect::<Vec<_>>();
+            let sheep = markers.iter().find(|m| m.1 == Marker::Sheep);
+            let wolf = markers.iter().find(|m| m.1 == Marker::Wolf);
+            for &(p, m, inside) in &markers {
+                let first = if m == Marker::Sheep { sheep } else { wolf }.unwrap();
+                if inside != first.2 {
+                    violations.push(Violation::Markers(first.0, p));
+                }
+            }
+            if let (Some(s), Some(w)) = (sheep, wolf) {
+                if s.2 == w.2 {
+                    violations.push(Violation::Markers(s.0, w.0));
+                }
+            }
+
+            // The vertices are visited from the top-left, so the first vertex
+            // found in each loop is its top-left vertex.
+            let mut visited = HashSet::new();
+            let mut loops = vec![];
+            for &v in &vertices {
+                if visited.contains(&v) || line_neighbors(self, v).is_empty() {
+                    continue;
+                }
+                loops.push(v);
+                let mut stack = vec![v];
+                while let Some(u) = stack.pop() {
+                    if visited.insert(u) {
+                        stack.extend(line_neighbors(self, u));
+                    }
+                }
+            }
+            match loops.len() {
+                0 => violations.push(Violation::NoLoop),
+                1 => {}
+                _ => violations.push(Violation::MultipleLoops(loops)),
+            }
+
+            violations
+        }
+    }
+}
+
+mod transform_impl {
+    use super::{Edge, Hint, HintSet, Marker, Puzzle, Side};
+    use geom::{Geom, Move, Point, Rotation, Size};
+
+    fn symmetries() -> [Rotation; 8] {
+        let flip = Rotation::H_FLIP;
+        [Rotation::CCW0,
+         Rotation::CCW90,
+         Rotation::CCW180,
+         Rotation::CCW270,
+         flip,
+         Rotation::CCW90 * flip,
+         Rotation::CCW180 * flip,
+         Rotation::CCW270 * flip]
+    }
 
-    struct CellRow<'a>(&'a Puzzle, i32);
+    // `q` is the edge in the doubled coordinates.
+    fn set_edge(puzzle: &mut Puzzle, q: Point, edge: Option<Edge>) {
+        if q.0 % 2 == 0 {
+            puzzle.set_edge_h(Point(q.0 / 2, (q.1 - 1) / 2), edge);
+        } else {
+            puzzle.set_edge_v(Point((q.0 - 1) / 2, q.1 / 2), edge);
+        }
+    }
+
+    // A tag followed by the little-endian bytes of the constraint, or nothing.
+    fn constraint_bytes(tag: u8, n: Option<u32>) -> Vec<u8> {
+        match n {
+            Some(n) => Some(tag).into_iter().chain((0..4).map(|i| (n >> (8 * i)) as u8)).collect(),
+            None => vec![],
+        }
+    }
+
+    type SortKey = (i32,
+                    i32,
+                    Option<u32>,
+                    Option<u32>,
+                    Vec<Hint>,
+                    Vec<bool>,
+                    Vec<Option<HintSet>>,
+                    Vec<Option<Marker>>,
+                    Vec<Hint>,
+                    Vec<Option<Side>>,
+                    Vec<Option<Edge>>,
+                    Vec<Option<Edge>>);
+
+    fn sort_key(puzzle: &Puzzle) -> SortKey {
+        let (rows, cols) = (puzzle.row(), puzzle.column());
+        let points = |rows: i32, cols: i32| {
+            (0..rows).flat_map(move |r| (0..cols).map(move |c| Point(r, c)))
+        };
+        (rows,
+         cols,
+         puzzle.loop_length(),
+         puzzle.area(),
+         points(rows, cols).map(|p| puzzle.hint(p)).collect(),
+         points(rows, cols).map(|p| puzzle.is_masked(p)).collect(),
+         points(rows, cols).map(|p| puzzle.hint_set(p)).collect(),
+         points(rows, cols).map(|p| puzzle.marker(p)).collect(),
+         points(rows + 1, cols + 1).map(|p| puzzle.vertex_hint(p)).collect(),
+         points(rows, cols).map(|p| puzzle.side(p)).collect(),
+         points(rows + 1, cols).map(|p| puzzle.edge_h(p)).collect(),
+         points(rows, cols + 1).map(|p| puzzle.edge_v(p)).collect())
+    }
+
+    impl Puzzle {
+        /// Returns the puzzle rotated or flipped by `rot`.
+        ///
+        /// The hints, the sides, the masks, the sets of the hints, the
+        /// markers, the vertex hints and the edges move together with the
+        /// cells, and the length and the area of the loop are kept.
+        pub fn rotate(&self, rot: Rotation) -> Puzzle {
+            let (rows, cols) = (self.row(), self.column());
+            let d = rot * Move(rows, cols);
+            let size = Size(d.0.abs(), d.1.abs());
+
+            // Works in the doubled coordinates, where the cell `Point(r, c)`
+            // is at `Point(2r + 1, 2c + 1)` and its top-left vertex is at
+            // `Point(2r, 2c)`, so that the center of the puzzle is at
+            // `Point(rows, columns)`.
+            let (center, new_center) = (Point(rows, cols), Point(size.0, size.1));
+            let map = |p: Point| new_center + rot * (p - center);
+
+            let mut puzzle = Puzzle::new(size);
+            puzzle.set_loop_length(self.loop_length());
+            puzzle.set_area(self.area());
+            for p in self.points() {
+                let q = map(Point(2 * p.0 + 1, 2 * p.1 + 1));
+                let q = Point(q.0 / 2, q.1 / 2);
+                puzzle.set_hint(q, self.hint(p));
+                puzzle.set_side(q, self.side(p));
+                puzzle.set_masked(q, self.is_masked(p));
+                puzzle.set_hint_set(q, self.hint_set(p));
+                puzzle.set_marker(q, self.marker(p));
+            }
+            for r in 0..(rows + 1) {
+                for c in 0..(cols + 1) {
+                    let q = map(Point(2 * r, 2 * c));
+                    puzzle.set_vertex_hint(Point(q.0 / 2, q.1 / 2),
+                                           self.vertex_hint(Point(r, c)));
+                }
+            }
+            for r in 0..(rows + 1) {
+                for c in 0..cols {
+                    set_edge(&mut puzzle,
+                             map(Point(2 * r, 2 * c + 1)),
+                             self.edge_h(Point(r, c)));
+                }
+            }
+            for r in 0..rows {
+                for c in 0..(cols + 1) {
+                    set_edge(&mut puzzle,
+                             map(Point(2 * r + 1, 2 * c)),
+                             self.edge_v(Point(r, c)));
+                }
+            }
+            puzzle
+        }
+
+        /// Returns the puzzle flipped left to right.
+        pub fn flip_horizontal(&self) -> Puzzle {
+            self.rotate(Rotation::H_FLIP)
+        }
+
+        /// Returns the puzzle flipped upside down.
+        pub fn flip_vertical(&self) -> Puzzle {
+            self.rotate(Rotation::V_FLIP)
+        }
+
+        /// Returns the puzzle flipped over its main diagonal, which swaps
+        /// the rows and the columns.
+        pub fn transpose(&self) -> Puzzle {
+            self.rotate(Rotation::CCW90 * Rotation::H_FLIP)
+        }
+
+        /// Returns the lexicographically smallest of the eight rotations and
+        /// reflections of the puzzle.
+        ///
+        /// The puzzles are compared by the size, the length and the area of
+        /// the loop, and then by the hints, the masks, the sets of the hints,
+        /// the markers, the vertex hints, the sides, the horizontal edges and
+        /// the vertical edges in row-major order. Puzzles which are rotations
+        /// or reflections of each other have the same canonical form.
+        pub fn canonical_form(&self) -> Puzzle {
+            symmetries()
+                .iter()
+                .map(|&rot| self.rotate(rot))
+                .min_by_key(sort_key)
+                .unwrap()
+        }
+
+        /// Returns a hash of the hints, the masks, the sets of the hints, the
+        /// markers, the vertex hints and the length and the area of the loop
+        /// of the puzzle, which is the same for all the rotations and
+        /// reflections of the puzzle.
+        ///
+        /// The edges and the sides are not hashed, so a puzzle and its
+        /// solution have the same fingerprint. The hash is computed with
+        /// 64-bit FNV-1a and does not change between runs or platforms.
+        pub fn fingerprint(&self) -> u64 {
+            let (rows, cols, length, area, hints, mask, sets, markers, vertex_hints) =
+                symmetries()
+                    .iter()
+                    .map(|&rot| {
+                        let key = sort_key(&self.rotate(rot));
+                        (key.0, key.1, key.2, key.3, key.4, key.5, key.6, key.7, key.8)
+                    })
+                    .min()
+                    .unwrap();
+            // The vertex hints and the constraints of the loop are hashed only
+            // if there are any, so that the fingerprints of the other puzzles
+            // are kept.
+            let vertex_hints = if vertex_hints.iter().any(|h| h.is_some()) {
+                vertex_hints
+            } else {
+                vec![]
+            };
+            let size = [rows, cols];
+            let cells = hints.iter().zip(&mask).zip(&sets).zip(&markers);
+            let bytes = size.iter()
+                            .flat_map(|&n| (0..4).map(move |i| (n >> (8 * i)) as u8))
+                            .chain(cells.map(|(((&h, &m), &s), &a)| {
+                                match (s, a) {
+                                    _ if m => 0xfe,
+                                    (_, Some(Marker::Sheep)) => 0xfd,
+                                    (_, Some(Marker::Wolf)) => 0xfc,
+                                    (Some(HintSet(bits)), None) => 0x20 | bits,
+                                    (None, None) => h.unwrap_or(0xff),
+                                }
+                            }))
+                            .chain(vertex_hints.iter().map(|h| h.unwrap_or(0xff)))
+                            .chain(constraint_bytes(0xfe, length))
+                            .chain(constraint_bytes(0xfd, area));
+            bytes.fold(0xcbf29ce484222325, |hash, b| {
+                (hash ^ (b as u64)).wrapping_mul(0x100000001b3)
+            })
+        }
+    }
+}
+
+mod region_impl {
+    use super::Puzzle;
+    use geom::{Geom, Move, Point, Size};
+
+    impl Puzzle {
+        /// Returns the part of the puzzle whose top-left cell is `origin`.
+        ///
+        /// The edges around the part are kept.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the part is not in the puzzle, or `size` is not positive.
+        pub fn crop(&self, origin: Point, size: Size) -> Puzzle {
+            assert!(self.contains(origin));
+            assert!(self.contains(origin + Move(size.0 - 1, size.1 - 1)));
+
+            let mut puzzle = Puzzle::new(size);
+            puzzle.copy_from(self, origin, Point(0, 0), size);
+            puzzle
+        }
+
+        /// Returns the puzzle of `size` with this puzzle put at `offset`.
+        ///
+        /// The cells out of this puzzle are empty.
+        ///
+        /// # Panics
+        ///
+        /// Panics if this puzzle put at `offset` does not fit in `size`.
+        pub fn embed(&self, size: Size, offset: Point) -> Puzzle {
+            let mut puzzle = Puzzle::new(size);
+            puzzle.paste(self, offset);
+            puzzle
+        }
+
+        /// Overwrites the part of the puzzle at `offset` with `other`.
+        ///
+        /// The hints, the sides, the masks, the sets of the hints, the markers
+        /// and the edges of the part, including the vertex hints and the edges
+        /// around it, are replaced by those of `other`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `other` put at `offset` does not fit in the puzzle.
+        pub fn paste(&mut self, other: &Puzzle, offset: Point) {
+            assert!(self.contains(offset));
+            assert!(self.contains(offset + Move(other.row() - 1, other.column() - 1)));
+
+            self.copy_from(other, Point(0, 0), offset, other.size());
+        }
+
+        /// Returns the puzzle with `other` put on the right of this puzzle.
+        ///
+        /// Both puzzles must have the same number of rows. The edges on the
+        /// border between them are those of `other`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the puzzles have the different numbers of rows.
+        pub fn join_horizontal(&self, other: &Puzzle) -> Puzzle {
+            assert_eq!(self.row(), other.row());
+            let size = Size(self.row(), self.column() + other.column());
+            let mut puzzle = self.embed(size, Point(0, 0));
+            puzzle.paste(other, Point(0, self.column()));
+            puzzle
+        }
+
+        /// Returns the puzzle with `other` put below this puzzle.
+        ///
+        /// Both puzzles must have the same number of columns. The edges on
+        /// the border between them are those of `other`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the puzzles have the different numbers of columns.
+        pub fn join_vertical(&self, other: &Puzzle) -> Puzzle {
+            assert_eq!(self.column(), other.column());
+            let size = Size(self.row() + other.row(), self.column());
+            let mut puzzle = self.embed(size, Point(0, 0));
+            puzzle.paste(other, Point(self.row(), 0));
+            puzzle
+        }
+
+        // Copies the part of `src` at `from` to the part of `self` at `to`.
+        fn copy_from(&mut self, src: &Puzzle, from: Point, to: Point, size: Size) {
+            let Size(rows, cols) = size;
+            let (s, d) = (from - Point(0, 0), to - Point(0, 0));
+
+            for r in 0..rows {
+                for c in 0..cols {
+                    let p = Point(r, c);
+                    self.set_hint(p + d, src.hint(p + s));
+                    self.set_side(p + d, src.side(p + s));
+                    self.set_masked(p + d, src.is_masked(p + s));
+                    self.set_hint_set(p + d, src.hint_set(p + s));
+                    self.set_marker(p + d, src.marker(p + s));
+                }
+            }
+            for r in 0..(rows + 1) {
+                for c in 0..(cols + 1) {
+                    let p = Point(r, c);
+                    self.set_vertex_hint(p + d, src.vertex_hint(p + s));
+                }
+            }
+            for r in 0..(rows + 1) {
+                for c in 0..cols {
+                    let p = Point(r, c);
+                    self.set_edge_h(p + d, src.edge_h(p + s));
+                }
+            }
+            for r in 0..rows {
+                for c in 0..(cols + 1) {
+                    let p = Point(r, c);
+                    self.set_edge_v(p + d, src.edge_v(p + s));
+                }
+            }
+        }
+    }
+}
+
+mod display_impl {
+    use super::{Edge, Marker, Puzzle, Side};
+    use std::fmt;
+    use geom::{Geom, Point};
+
+    struct Cross<'a>(&'a Puzzle, Point);
+    impl<'a> fmt::Display for Cross<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let Cross(puzzle, p) = *self;
+            match puzzle.vertex_hint[p] {
+                Some(n) => write!(f, "{}", n),
+                None => write!(f, "+"),
+            }
+        }
+    }
+
+    struct HEdge<'a>(&'a Puzzle, Point, usize);
+    impl<'a> fmt::Display for HEdge<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let HEdge(puzzle, p, width) = *self;
+            let c = match puzzle.edge_h[p] {
+                Some(Edge::Cross) => "x",
+                Some(Edge::Line) => "-",
+                None => " ",
+            };
+            for _ in 0..width {
+                try!(write!(f, "{}", c));
+            }
+            Ok(())
+        }
+    }
+
+    struct VEdge<'a>(&'a Puzzle, Point);
+    impl<'a> fmt::Display for VEdge<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let VEdge(puzzle, p) = *self;
+            match puzzle.edge_v[p] {
+                Some(Edge::Cross) => try!(write!(f, "x")),
+                Some(Edge::Line) => try!(write!(f, "|")),
+                None => try!(write!(f, " ")),
+            }
+            Ok(())
+        }
+    }
+
+    struct EdgeRow<'a>(&'a Puzzle, i32, usize);
+    impl<'a> fmt::Display for EdgeRow<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let EdgeRow(puzzle, r, width) = *self;
+            for c in 0..puzzle.column() {
+                let p = Point(r, c);
+                try!(write!(f, "{}", Cross(puzzle, p)));
+                try!(write!(f, "{}", HEdge(puzzle, p, width)));
+            }
+            try!(write!(f, "{}", Cross(puzzle, Point(r, puzzle.column()))));
+            Ok(())
+        }
+    }
+
+    fn hint_str(puzzle: &Puzzle, p: Point) -> String {
+        match (puzzle.hint[p], puzzle.hint_set[p], puzzle.marker[p]) {
+            _ if puzzle.mask[p] => "#".to_string(),
+            (_, _, Some(Marker::Sheep)) => "s".to_string(),
+            (_, _, Some(Marker::Wolf)) => "w".to_string(),
+            (_, Some(set), None) => set.to_string(),
+            (Some(n), None, None) => n.to_string(),
+            (None, None, None) => " ".to_string(),
+        }
+    }
+
+    struct CellRow<'a>(&'a Puzzle, i32, usize, bool);
     impl<'a> fmt::Display for CellRow<'a> {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            let CellRow(puzzle, r) = *self;
+            let CellRow(puzzle, r, hint_width, with_side) = *self;
             for c in 0..puzzle.column() {
                 let p = Point(r, c);
                 try!(write!(f, "{}", VEdge(puzzle, p)));
-                match puzzle.hint[p] {
-                    Some(n) => try!(write!(f, "{}", n)),
-                    None => try!(write!(f, " ")),
+                try!(write!(f, "{:1$}", hint_str(puzzle, p), hint_width));
+                if with_side {
+                    match puzzle.side[p] {
+                        Some(Side::In) => try!(write!(f, "i")),
+                        Some(Side::Out) => try!(write!(f, "o")),
+                        None => try!(write!(f, " ")),
+                    }
                 }
             }
             try!(write!(f, "{}", VEdge(puzzle, Point(r, puzzle.column()))));
@@ -421,22 +1897,126 @@ mod display_impl {
 
     impl fmt::Display for Puzzle {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            // Cells are as wide as the widest set of the hints, and one more
+            // character wide if any of them has its side, so that the side
+            // marker can be written next to the hint.
+            let with_side = self.points().any(|p| self.side[p].is_some());
+            let hint_width = self.points().map(|p| hint_str(self, p).len()).max().unwrap_or(1);
+            let width = if with_side { hint_width + 1 } else { hint_width };
+            if let Some(length) = self.loop_length {
+                try!(writeln!(f, "length: {}", length));
+            }
+            if let Some(area) = self.area {
+                try!(writeln!(f, "area: {}", area));
+            }
             for r in 0..self.row() {
-                try!(writeln!(f, "{}", EdgeRow(self, r)));
-                try!(writeln!(f, "{}", CellRow(self, r)));
+                try!(writeln!(f, "{}", EdgeRow(self, r, width)));
+                try!(writeln!(f, "{}", CellRow(self, r, hint_width, with_side)));
             }
-            try!(writeln!(f, "{}", EdgeRow(self, self.row())));
+            try!(writeln!(f, "{}", EdgeRow(self, self.row(), width)));
             Ok(())
         }
     }
 }
 
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use serde::{Serialize, Serializer};
+    use geom::{Geom, Point};
+    use super::{Edge, Marker, Puzzle, Side};
+
+    impl Serialize for Side {
+        fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+            match *self {
+                Side::In => serializer.serialize_str("in"),
+                Side::Out => serializer.serialize_str("out"),
+            }
+        }
+    }
+
+    impl Serialize for Edge {
+        fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+            match *self {
+                Edge::Line => serializer.serialize_str("line"),
+                Edge::Cross => serializer.serialize_str("cross"),
+            }
+        }
+    }
+
+    impl Serialize for Marker {
+        fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+            match *self {
+                Marker::Sheep => serializer.serialize_str("sheep"),
+                Marker::Wolf => serializer.serialize_str("wolf"),
+            }
+        }
+    }
+
+    struct Size(i32, i32);
+
+    impl Serialize for Size {
+        fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+            let mut state = try!(serializer.serialize_struct("Size", 2));
+            try!(serializer.serialize_struct_elt(&mut state, "rows", self.0));
+            try!(serializer.serialize_struct_elt(&mut state, "columns", self.1));
+            serializer.serialize_struct_end(state)
+        }
+    }
+
+    fn table<T, F>(rows: i32, columns: i32, f: F) -> Vec<Vec<T>>
+        where F: Fn(Point) -> T
+    {
+        (0..rows).map(|r| (0..columns).map(|c| f(Point(r, c))).collect()).collect()
+    }
+
+    impl Serialize for Puzzle {
+        fn serialize<S: Serializer>(&self, serializer: &mut S) -> Result<(), S::Error> {
+            let (rows, cols) = (self.row(), self.column());
+            let mut state = try!(serializer.serialize_struct("Puzzle", 11));
+            try!(serializer.serialize_struct_elt(&mut state, "size", Size(rows, cols)));
+            try!(serializer.serialize_struct_elt(&mut state, "loop_length", self.loop_length));
+            try!(serializer.serialize_struct_elt(&mut state, "area", self.area));
+            try!(serializer.serialize_struct_elt(&mut state,
+                                                 "hints",
+                                                 table(rows, cols, |p| self.hint(p))));
+            try!(serializer.serialize_struct_elt(&mut state,
+                                                 "sides",
+                                                 table(rows, cols, |p| self.side(p))));
+            try!(serializer.serialize_struct_elt(&mut state,
+                                                 "masked",
+                                                 table(rows, cols, |p| self.is_masked(p))));
+            try!(serializer.serialize_struct_elt(&mut state,
+                                                 "hint_sets",
+                                                 table(rows, cols, |p| {
+                                                     self.hint_set(p).map(|s| s.values())
+                                                 })));
+            try!(serializer.serialize_struct_elt(&mut state,
+                                                 "markers",
+                                                 table(rows, cols, |p| self.marker(p))));
+            try!(serializer.serialize_struct_elt(&mut state,
+                                                 "vertex_hints",
+                                                 table(rows + 1, cols + 1, |p| {
+                                                     self.vertex_hint(p)
+                                                 })));
+            try!(serializer.serialize_struct_elt(&mut state,
+                                                 "edges_h",
+                                                 table(rows + 1, cols, |p| self.edge_h(p))));
+            try!(serializer.serialize_struct_elt(&mut state,
+                                                 "edges_v",
+                                                 table(rows, cols + 1, |p| self.edge_v(p))));
+            serializer.serialize_struct_end(state)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::fmt;
     use std::error::Error;
-    use super::{ParsePuzzleError, ParsePuzzleResult, Puzzle};
-    use geom::{Geom, Point, Size};
+    use super::{Edge, HintSet, LoopPathError, Marker, ParseMode, ParsePuzzleError,
+                ParsePuzzleResult, Puzzle, Side, Violation};
+    use diagnostic::Span;
+    use geom::{Geom, Point, Rotation, Size};
 
     fn check_error<T>(result: ParsePuzzleResult<T>, error: ParsePuzzleError)
         where T: fmt::Debug
@@ -506,6 +2086,84 @@ ______
         assert_eq!(output, puzzle.to_string());
     }
 
+    #[test]
+    fn parse_side() {
+        let input = "+--+--+xx+
+|i3   x  x
++--+--+xx+
+x  xo x0ox
++xx+xx+xx+
+";
+        let output = "+--+--+xx+
+|3i   x  x
++--+--+xx+
+x  x ox0ox
++xx+xx+xx+
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(Some(Side::In), puzzle.side(Point(0, 0)));
+        assert_eq!(None, puzzle.side(Point(0, 1)));
+        assert_eq!(Some(Side::Out), puzzle.side(Point(1, 1)));
+        assert_eq!(Some(0), puzzle.hint(Point(1, 2)));
+        assert_eq!(Some(Side::Out), puzzle.side(Point(1, 2)));
+        assert_eq!(output, puzzle.to_string());
+        assert_eq!(puzzle, output.parse::<Puzzle>().unwrap());
+        assert_eq!(Some(Side::In),
+                   "+-+\n|i3|\n+-+\n".parse::<Puzzle>().unwrap().side(Point(0, 0)));
+    }
+
+    #[test]
+    fn parse_pretty() {
+        let input = "   0  1
+  +--+~~+
+0 |3    ?0
+  +--+  .
+   0  1
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(Size(1, 2), puzzle.size());
+        assert_eq!(Some(3), puzzle.hint(Point(0, 0)));
+        assert_eq!(None, puzzle.hint(Point(0, 1)));
+        assert_eq!(Some(Edge::Line), puzzle.edge_h(Point(0, 0)));
+        assert_eq!(None, puzzle.edge_h(Point(0, 1)));
+        assert_eq!(Some(Edge::Line), puzzle.edge_h(Point(1, 0)));
+        assert_eq!(Some(Edge::Cross), puzzle.edge_h(Point(1, 1)));
+        assert_eq!(Some(Edge::Line), puzzle.edge_v(Point(0, 0)));
+        assert_eq!(Some(Edge::Cross), puzzle.edge_v(Point(0, 1)));
+        assert_eq!(None, puzzle.edge_v(Point(0, 2)));
+
+        let input = "   0  1
+  +--+
+0 |1 |#
+  +--+
+   0  1
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(Some(1), puzzle.hint(Point(0, 0)));
+        assert!(!puzzle.is_masked(Point(0, 0)));
+        assert!(puzzle.is_masked(Point(0, 1)));
+        assert!(!puzzle.is_drawn_edge_h(Point(0, 1)));
+        assert!(puzzle.is_drawn_edge_v(Point(0, 1)));
+        assert!(!puzzle.is_drawn_vertex(Point(0, 2)));
+
+        let err = "   0  1\n  +--+~~+\n0 |5    ?0\n  +--+  .\n"
+                      .parse::<Puzzle>()
+                      .unwrap_err();
+        assert_eq!(Some(Span::new(2, 3, 2)), err.span());
+    }
+
+    #[test]
+    fn parse_box_drawing() {
+        let input = "\u{250c}\u{2500}\u{2510}
+\u{2502}3\u{2502}
+\u{2514}\u{2500}\u{2518}
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(Some(3), puzzle.hint(Point(0, 0)));
+        assert_eq!(Some(Edge::Line), puzzle.edge_h(Point(1, 0)));
+        assert_eq!(Some(Edge::Line), puzzle.edge_v(Point(0, 1)));
+    }
+
     #[test]
     fn parse_pattern1_noedge() {
         let input = "
@@ -543,9 +2201,462 @@ ______
                     ParsePuzzleError::invalid_hint());
         check_error("1253".parse::<Puzzle>(), ParsePuzzleError::invalid_hint());
     }
+    #[test]
+    fn parse_error_span() {
+        let span = |s: &str| s.parse::<Puzzle>().unwrap_err().span();
+        assert_eq!(Some(Span::new(2, 2, 1)), span("\n3___\n_2a_\n"));
+        assert_eq!(Some(Span::new(1, 0, 3)), span("3___\n_2_\n"));
+        assert_eq!(Some(Span::new(1, 3, 1)),
+                   span("+-+-+\n|3|5 \n+-+-+\n"));
+        assert_eq!(Some(Span::new(2, 2, 1)),
+                   span("+-+-+\n|3| |\n+-|-+\n"));
+        assert_eq!(None, span(""));
+
+        let err = "3___\n_2a_\n".parse::<Puzzle>().unwrap_err();
+        assert_eq!("invalid hint found in string at line 2, column 3",
+                   err.to_string());
+    }
+
+    #[test]
+    fn parse_strict() {
+        let input = "
++--+ +-+!!+asdf
++  + + +xx+
+|  |1|    x
+|  | |  2 x
++  + + +  +
+";
+        let err = Puzzle::parse_with_mode(input, ParseMode::Strict).unwrap_err();
+        assert_eq!(ParsePuzzleError::text_outside_lattice().description(),
+                   err.description());
+        assert_eq!(Some(Span::new(1, 11, 4)), err.span());
+
+        let input = "+-+-+\n|3! |\n+-+-+\n";
+        assert!(input.parse::<Puzzle>().is_ok());
+        let err = Puzzle::parse_with_mode(input, ParseMode::Strict).unwrap_err();
+        assert_eq!(ParsePuzzleError::invalid_edge().description(),
+                   err.description());
+        assert_eq!(Some(Span::new(1, 2, 1)), err.span());
+
+        let input = "+-+ +\n|3|  \n+-+x+\n";
+        assert_eq!(input.parse::<Puzzle>().unwrap(),
+                   Puzzle::parse_with_mode(input, ParseMode::Strict).unwrap());
+    }
+
+    #[test]
+    fn loop_path() {
+        let input = "+-+-+
+| x |
++x+-+
+| |
++-+ +
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(vec![Point(0, 0), Point(0, 1), Point(0, 2), Point(1, 2), Point(1, 1),
+                        Point(2, 1), Point(2, 0), Point(1, 0)],
+                   puzzle.loop_path().unwrap());
+
+        let puzzle = "+-+x+-+\n| | | |\n+-+x+-+\n".parse::<Puzzle>().unwrap();
+        let err = puzzle.loop_path().unwrap_err();
+        assert_eq!(LoopPathError::multiple_loops().description(), err.description());
+
+        let puzzle = "+-+-+\n| x x\n+-+-+\n".parse::<Puzzle>().unwrap();
+        let err = puzzle.loop_path().unwrap_err();
+        assert_eq!(LoopPathError::invalid_degree(Point(0, 2)).description(),
+                   err.description());
+        assert_eq!(Some(Point(0, 2)), err.vertex());
+
+        let err = Puzzle::new(Size(1, 1)).loop_path().unwrap_err();
+        assert_eq!(LoopPathError::no_loop().description(), err.description());
+    }
+
+    #[test]
+    fn check_solution() {
+        let puzzle = "+-+-+\n|2x |\n+x+-+\n| |  \n+-+ +\n".parse::<Puzzle>().unwrap();
+        assert_eq!(Vec::<Violation>::new(), puzzle.check_solution());
+
+        let puzzle = "+-+x+-+\n|2| |0|\n+-+x+-+\n".parse::<Puzzle>().unwrap();
+        assert_eq!(vec![Violation::Hint {
+                            cell: Point(0, 0),
+                            expected: 2,
+                            actual: 4,
+                        },
+                        Violation::Hint {
+                            cell: Point(0, 2),
+                            expected: 0,
+                            actual: 4,
+                        },
+                        Violation::MultipleLoops(vec![Point(0, 0), Point(0, 2)])],
+                   puzzle.check_solution());
+
+        let puzzle = "+-+-+\n| x x\n+-+-+\n".parse::<Puzzle>().unwrap();
+        assert_eq!(vec![Violation::Degree {
+                            vertex: Point(0, 2),
+                            degree: 1,
+                        },
+                        Violation::Degree {
+                            vertex: Point(1, 2),
+                            degree: 1,
+                        }],
+                   puzzle.check_solution());
+
+        assert_eq!(vec![Violation::NoLoop], Puzzle::new(Size(1, 1)).check_solution());
+    }
+
+    #[test]
+    fn transform() {
+        let input = "+-+x+
+|3x1x
++ + +
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+
+        let rotated = puzzle.rotate(Rotation::CCW90);
+        assert_eq!("+x+
+x1 
++x+
+|3 
++-+
+",
+                   rotated.to_string());
+        assert_eq!(puzzle, rotated.rotate(Rotation::CCW270));
+
+        assert_eq!("+x+-+
+x1x3|
++ + +
+",
+                   puzzle.flip_horizontal().to_string());
+        assert_eq!("+ + +
+|3x1x
++-+x+
+",
+                   puzzle.flip_vertical().to_string());
+        assert_eq!("+-+
+|3 
++x+
+x1 
++x+
+",
+                   puzzle.transpose().to_string());
+        assert_eq!(puzzle, puzzle.transpose().transpose());
+    }
+
+    #[test]
+    fn canonical_form() {
+        let puzzle = "3_1\n__2\n".parse::<Puzzle>().unwrap();
+        let canonical = puzzle.canonical_form();
+        for &rot in &[Rotation::CCW90, Rotation::CCW180, Rotation::H_FLIP, Rotation::V_FLIP] {
+            assert_eq!(canonical, puzzle.rotate(rot).canonical_form());
+        }
+        assert_eq!(canonical, puzzle.transpose().canonical_form());
+        assert_eq!(Size(2, 3), canonical.size());
+        assert_eq!(None, canonical.hint(Point(0, 0)));
+
+        let other = "3_1\n_2_\n".parse::<Puzzle>().unwrap();
+        assert!(canonical != other.canonical_form());
+    }
+
+    #[test]
+    fn fingerprint() {
+        let puzzle = "3_1\n__2\n".parse::<Puzzle>().unwrap();
+        let fingerprint = puzzle.fingerprint();
+        for &rot in &[Rotation::CCW90, Rotation::CCW180, Rotation::CCW270, Rotation::H_FLIP] {
+            assert_eq!(fingerprint, puzzle.rotate(rot).fingerprint());
+        }
+        assert_eq!(fingerprint, puzzle.transpose().fingerprint());
+
+        let solved = "+-+-+-+\n|3   1|\n+ + + +\n|    2|\n+-+-+-+\n".parse::<Puzzle>().unwrap();
+        assert_eq!(fingerprint, solved.fingerprint());
+
+        assert!(fingerprint != "3_1\n_2_\n".parse::<Puzzle>().unwrap().fingerprint());
+        assert!(fingerprint != "3_1\n__2\n__0\n".parse::<Puzzle>().unwrap().fingerprint());
+    }
+
+    #[test]
+    fn region() {
+        let puzzle = "+-+x+
+|3x1x
++ + +
+"
+                         .parse::<Puzzle>()
+                         .unwrap();
+
+        let cropped = puzzle.crop(Point(0, 1), Size(1, 1));
+        assert_eq!("+x+
+x1x
++ +
+",
+                   cropped.to_string());
+
+        let embedded = cropped.embed(Size(2, 2), Point(1, 0));
+        assert_eq!("+ + +
+     
++x+ +
+x1x  
++ + +
+",
+                   embedded.to_string());
+        assert_eq!(cropped, embedded.crop(Point(1, 0), Size(1, 1)));
+
+        let joined = puzzle.join_horizontal(&cropped);
+        assert_eq!("+-+x+x+
+|3x1x1x
++ + + +
+",
+                   joined.to_string());
+        assert_eq!(puzzle, joined.crop(Point(0, 0), Size(1, 2)));
+
+        let joined = puzzle.join_vertical(&puzzle);
+        assert_eq!(Size(2, 2), joined.size());
+        assert_eq!(puzzle, joined.crop(Point(1, 0), Size(1, 2)));
+        assert_eq!(Some(Edge::Line), joined.edge_h(Point(1, 0)));
+    }
+
+    #[test]
+    fn parse_masked() {
+        let puzzle = "#3\n3_\n".parse::<Puzzle>().unwrap();
+        assert!(puzzle.is_masked(Point(0, 0)));
+        assert!(!puzzle.is_masked(Point(0, 1)));
+        assert!(!puzzle.is_on_board(Point(0, 0)));
+        assert!(puzzle.is_on_board(Point(1, 1)));
+        assert!(!puzzle.is_on_board(Point(2, 1)));
+        assert_eq!(None, puzzle.hint(Point(0, 0)));
+
+        let input = "+xx+--+
+x#o|3i|
++--+xx+
+|3ix i|
++--+--+
+";
+        let solved = input.parse::<Puzzle>().unwrap();
+        assert!(solved.is_masked(Point(0, 0)));
+        assert_eq!(Some(Side::Out), solved.side(Point(0, 0)));
+        assert_eq!(input, solved.to_string());
+        assert_eq!(Vec::<Violation>::new(), solved.check_solution());
+
+        let enclosed = "+-+-+\n|#  |\n+-+-+\n".parse::<Puzzle>().unwrap();
+        assert_eq!(vec![Violation::Masked { cell: Point(0, 0) }],
+                   enclosed.check_solution());
+    }
+
+    #[test]
+    fn parse_vertex_hint() {
+        let input = "+-2x+
+|4| x
++-+x0
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(Some(2), puzzle.vertex_hint(Point(0, 1)));
+        assert_eq!(Some(0), puzzle.vertex_hint(Point(1, 2)));
+        assert_eq!(None, puzzle.vertex_hint(Point(0, 0)));
+        assert_eq!(Some(4), puzzle.hint(Point(0, 0)));
+        assert_eq!(input, puzzle.to_string());
+        assert_eq!(Vec::<Violation>::new(), puzzle.check_solution());
+
+        let rotated = puzzle.rotate(Rotation::CCW90);
+        assert_eq!(Some(2), rotated.vertex_hint(Point(1, 0)));
+        assert_eq!(Some(0), rotated.vertex_hint(Point(0, 1)));
+        assert_eq!(puzzle.fingerprint(), rotated.fingerprint());
+        assert!(puzzle.fingerprint() != "3_\n".parse::<Puzzle>().unwrap().fingerprint());
+
+        let broken = "+-+-3\n|3  |\n+-+-+\n".parse::<Puzzle>().unwrap();
+        assert_eq!(vec![Violation::VertexHint {
+                            vertex: Point(0, 2),
+                            expected: 3,
+                            actual: 2,
+                        }],
+                   broken.check_solution());
+
+        let err = "+ 5 +\n     \n+ + +\n".parse::<Puzzle>().unwrap_err();
+        assert_eq!(ParsePuzzleError::invalid_hint().description(),
+                   err.description());
+        assert_eq!(Some(Span::new(0, 2, 1)), err.span());
+    }
+
+    #[test]
+    fn parse_hinted_vertex_lines() {
+        let input = "2-2
+| |
++-+
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(Size(1, 1), puzzle.size());
+        assert_eq!(Some(2), puzzle.vertex_hint(Point(0, 0)));
+        assert_eq!(Some(2), puzzle.vertex_hint(Point(0, 1)));
+        assert_eq!(input, puzzle.to_string());
+
+        let input = "2-+
+| |
+2-+
+| |
+2-+
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(Size(2, 1), puzzle.size());
+        assert_eq!(input, puzzle.to_string());
+
+        let input = "2-2
+| |
+2-2
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(Size(1, 1), puzzle.size());
+        assert_eq!(Some(2), puzzle.vertex_hint(Point(1, 1)));
+        assert_eq!(input, puzzle.to_string());
+
+        // The lines which are the hints of the cells stay so.
+        let puzzle = "2-2\n0-0\n".parse::<Puzzle>().unwrap();
+        assert_eq!(Size(2, 3), puzzle.size());
+        assert_eq!(Some(0), puzzle.hint(Point(1, 0)));
+    }
+
+    #[test]
+    fn parse_hint_set() {
+        let input = "+--+xx+
+|12|? x
++--+xx+
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(Some(HintSet::new(&[1, 2])), puzzle.hint_set(Point(0, 0)));
+        assert_eq!(Some(HintSet::any()), puzzle.hint_set(Point(0, 1)));
+        assert_eq!(None, puzzle.hint(Point(0, 0)));
+        assert_eq!(input, puzzle.to_string());
+        assert_eq!(vec![Violation::HintSet {
+                            cell: Point(0, 0),
+                            expected: HintSet::new(&[1, 2]),
+                            actual: 4,
+                        }],
+                   puzzle.check_solution());
+        assert!(puzzle.fingerprint() != "__\n".parse::<Puzzle>().unwrap().fingerprint());
+
+        let set = HintSet::new(&[3, 0]);
+        assert_eq!((0, 3), (set.min(), set.max()));
+        assert_eq!(vec![0, 3], set.values());
+        assert_eq!("03", set.to_string());
+
+        let puzzle = "?2\n".parse::<Puzzle>().unwrap();
+        assert_eq!(Some(HintSet::any()), puzzle.hint_set(Point(0, 0)));
+        assert_eq!(Some(2), puzzle.hint(Point(0, 1)));
+
+        let err = "+--+\n|15|\n+--+\n".parse::<Puzzle>().unwrap_err();
+        assert_eq!(ParsePuzzleError::invalid_hint().description(),
+                   err.description());
+    }
+
+    #[test]
+    fn parse_loop_constraint() {
+        let input = "length: 6
+area: 2
++-+-+
+|3 3|
++-+-+
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(Some(6), puzzle.loop_length());
+        assert_eq!(Some(2), puzzle.area());
+        assert_eq!(input, puzzle.to_string());
+        assert_eq!(Vec::<Violation>::new(), puzzle.check_solution());
+        assert_eq!(Some(6), puzzle.rotate(Rotation::CCW90).loop_length());
+        assert!(puzzle.fingerprint() != "33\n".parse::<Puzzle>().unwrap().fingerprint());
+
+        let mut broken = puzzle.clone();
+        broken.set_loop_length(Some(4));
+        broken.set_area(Some(1));
+        assert_eq!(vec![Violation::LoopLength {
+                            expected: 4,
+                            actual: 6,
+                        },
+                        Violation::Area {
+                            expected: 1,
+                            actual: 2,
+                        }],
+                   broken.check_solution());
+
+        let puzzle = "\narea: 3\n\n3_\n".parse::<Puzzle>().unwrap();
+        assert_eq!(None, puzzle.loop_length());
+        assert_eq!(Some(3), puzzle.area());
+        assert_eq!(Some(3), puzzle.hint(Point(0, 0)));
+
+        let err = "length: 6\narea: x\n3_\n".parse::<Puzzle>().unwrap_err();
+        assert_eq!(ParsePuzzleError::invalid_constraint().description(),
+                   err.description());
+        assert_eq!(Some(Span::new(1, 0, 7)), err.span());
+        let err = "length: 6\n3_\n5_\n".parse::<Puzzle>().unwrap_err();
+        assert_eq!(Some(Span::new(2, 0, 1)), err.span());
+    }
+
+    #[test]
+    fn parse_marker() {
+        let input = "+-+x+x+
+|s|wxsx
++-+x+x+
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(Some(Marker::Sheep), puzzle.marker(Point(0, 0)));
+        assert_eq!(Some(Marker::Wolf), puzzle.marker(Point(0, 1)));
+        assert_eq!(None, puzzle.hint(Point(0, 1)));
+        assert_eq!(input, puzzle.to_string());
+        assert_eq!(vec![Violation::Markers(Point(0, 0), Point(0, 2))],
+                   puzzle.check_solution());
+
+        let rotated = puzzle.rotate(Rotation::CCW90);
+        assert_eq!(Some(Marker::Wolf), rotated.marker(Point(1, 0)));
+        assert_eq!(puzzle.fingerprint(), rotated.fingerprint());
+
+        let puzzle = "sw_\n".parse::<Puzzle>().unwrap();
+        assert_eq!(Some(Marker::Sheep), puzzle.marker(Point(0, 0)));
+        assert_eq!(Some(Marker::Wolf), puzzle.marker(Point(0, 1)));
+        assert_eq!(None, puzzle.marker(Point(0, 2)));
+    }
+
+    #[test]
+    fn parse_nfp() {
+        let input = "dataVersion=1&dataTitle=Sample&\n\
+                     dataQuestion=3+_+_%0D%0A_+2+1%0D%0A&dataAnswer=&\n";
+        let puzzle = Puzzle::from_nfp(input).unwrap();
+        assert_eq!(Size(2, 3), puzzle.size());
+        assert_eq!(Some(3), puzzle.hint(Point(0, 0)));
+        assert_eq!(None, puzzle.hint(Point(0, 1)));
+        assert_eq!(None, puzzle.hint(Point(1, 0)));
+        assert_eq!(Some(2), puzzle.hint(Point(1, 1)));
+        assert_eq!(Some(1), puzzle.hint(Point(1, 2)));
+
+        check_error(Puzzle::from_nfp("dataVersion=1&dataAnswer="),
+                    ParsePuzzleError::no_nfp_question());
+        check_error(Puzzle::from_nfp("dataQuestion=1%2"),
+                    ParsePuzzleError::invalid_nfp_encoding());
+        check_error(Puzzle::from_nfp("dataQuestion=1%zz"),
+                    ParsePuzzleError::invalid_nfp_encoding());
+        check_error(Puzzle::from_nfp("dataQuestion="), ParsePuzzleError::empty());
+    }
+
     #[test]
     fn parse_pattern2_length_mismatch() {
         check_error("1111\n222".parse::<Puzzle>(),
                     ParsePuzzleError::length_mismatch());
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_json() {
+        use serde_json;
+
+        let input = "+-+ +
+|3x
++ + +
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        let expected = "{\"size\":{\"rows\":1,\"columns\":2},\
+                        \"loop_length\":null,\
+                        \"area\":null,\
+                        \"hints\":[[3,null]],\
+                        \"sides\":[[null,null]],\
+                        \"masked\":[[false,false]],\
+                        \"hint_sets\":[[null,null]],\
+                        \"markers\":[[null,null]],\
+                        \"vertex_hints\":[[null,null,null],[null,null,null]],\
+                        \"edges_h\":[[\"line\",null],[null,null]],\
+                        \"edges_v\":[[\"line\",\"cross\",null]]}";
+        assert_eq!(expected, serde_json::to_string(&puzzle).unwrap());
+    }
 }
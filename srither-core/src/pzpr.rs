@@ -0,0 +1,605 @@
+// Copyright (c) 2016 srither-core developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Conversion between puzzles and the pzprjs (puzz.link) formats.
+//!
+//! Both the URL format and the `pzprv3` file format are supported. The
+//! question mark hints of pzprjs are read as `HintSet::any()`, which is the
+//! only set of the numbers that can be written.
+
+use std::error::Error;
+use std::fmt;
+
+use geom::{Geom, Point, Size};
+use puzzle::{Edge, HintSet, Puzzle};
+
+const URL_PREFIX: &'static str = "https://puzz.link/p?";
+const FILE_HEADER: &'static str = "pzprv3";
+const PUZZLE_TYPE: &'static str = "slither";
+
+/// An error type which is returned from parsing a pzprjs string into puzzle.
+#[derive(Copy, Clone, Debug)]
+pub struct ParsePzprError {
+    kind: PzprErrorKind,
+}
+
+/// pzprjs parse result.
+pub type ParsePzprResult<T> = Result<T, ParsePzprError>;
+
+#[derive(Copy, Clone, Debug)]
+enum PzprErrorKind {
+    InvalidHeader,
+    UnsupportedType,
+    InvalidSize,
+    InvalidData,
+}
+
+impl Error for ParsePzprError {
+    fn description(&self) -> &str {
+        use self::PzprErrorKind::*;
+        match self.kind {
+            InvalidHeader => "cannot found pzprv3 header in string",
+            UnsupportedType => "the puzzle type is not slither link",
+            InvalidSize => "invalid board size found in string",
+            InvalidData => "invalid board data found in string",
+        }
+    }
+}
+
+impl fmt::Display for ParsePzprError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(f)
+    }
+}
+
+impl ParsePzprError {
+    fn invalid_header() -> ParsePzprError {
+        ParsePzprError { kind: PzprErrorKind::InvalidHeader }
+    }
+    fn unsupported_type() -> ParsePzprError {
+        ParsePzprError { kind: PzprErrorKind::UnsupportedType }
+    }
+    fn invalid_size() -> ParsePzprError {
+        ParsePzprError { kind: PzprErrorKind::InvalidSize }
+    }
+    fn invalid_data() -> ParsePzprError {
+        ParsePzprError { kind: PzprErrorKind::InvalidData }
+    }
+}
+
+/// An error type which is returned from encoding a puzzle into a pzprjs
+/// string, if the puzzle has what pzprjs cannot hold.
+#[derive(Copy, Clone, Debug)]
+pub struct EncodePzprError {
+    kind: EncodePzprErrorKind,
+}
+
+/// pzprjs encode result.
+pub type EncodePzprResult<T> = Result<T, EncodePzprError>;
+
+#[derive(Copy, Clone, Debug)]
+enum EncodePzprErrorKind {
+    Masked,
+    VertexHint,
+    Marker,
+    HintSet,
+    Constraint,
+}
+
+impl Error for EncodePzprError {
+    fn description(&self) -> &str {
+        use self::EncodePzprErrorKind::*;
+        match self.kind {
+            Masked => "masked cells cannot be encoded into pzprjs",
+            VertexHint => "hints of the vertices cannot be encoded into pzprjs",
+            Marker => "sheep and wolves cannot be encoded into pzprjs",
+            HintSet => "sets of the numbers other than `?` cannot be encoded into pzprjs",
+            Constraint => "the loop length and the area cannot be encoded into pzprjs",
+        }
+    }
+}
+
+impl fmt::Display for EncodePzprError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.description().fmt(f)
+    }
+}
+
+impl EncodePzprError {
+    fn masked() -> EncodePzprError {
+        EncodePzprError { kind: EncodePzprErrorKind::Masked }
+    }
+    fn vertex_hint() -> EncodePzprError {
+        EncodePzprError { kind: EncodePzprErrorKind::VertexHint }
+    }
+    fn marker() -> EncodePzprError {
+        EncodePzprError { kind: EncodePzprErrorKind::Marker }
+    }
+    fn hint_set() -> EncodePzprError {
+        EncodePzprError { kind: EncodePzprErrorKind::HintSet }
+    }
+    fn constraint() -> EncodePzprError {
+        EncodePzprError { kind: EncodePzprErrorKind::Constraint }
+    }
+}
+
+fn url_query(s: &str) -> &str {
+    let s = s.trim();
+    match s.rfind('?') {
+        Some(i) => &s[i + 1..],
+        None => s,
+    }
+}
+
+/// Returns true if the string looks like a pzprjs slither link URL.
+pub fn is_url(s: &str) -> bool {
+    let s = s.trim();
+    !s.contains(char::is_whitespace) && url_query(s).starts_with("slither/")
+}
+
+/// Parses a pzprjs slither link URL (`?slither/<cols>/<rows>/<body>`) into
+/// puzzle.
+///
+/// Both the full URL and the bare query part are accepted.
+pub fn parse_url(s: &str) -> ParsePzprResult<Puzzle> {
+    use self::ParsePzprError as Error;
+
+    let mut it = url_query(s).split('/');
+    if it.next() != Some(PUZZLE_TYPE) {
+        return Err(Error::unsupported_type());
+    }
+
+    let cols = try!(parse_size(it.next()));
+    let rows = try!(parse_size(it.next()));
+    let body = it.next().unwrap_or("");
+
+    let mut puzzle = Puzzle::new(Size(rows, cols));
+    try!(decode_hint(&mut puzzle, body));
+    Ok(puzzle)
+}
+
+/// Encodes the hints of the puzzle into pzprjs slither link URL.
+pub fn to_url(puzzle: &Puzzle) -> EncodePzprResult<String> {
+    try!(check_encodable(puzzle));
+    Ok(format!("{}{}/{}/{}/{}",
+               URL_PREFIX,
+               PUZZLE_TYPE,
+               puzzle.column(),
+               puzzle.row(),
+               encode_hint(puzzle)))
+}
+
+// The slither link of pzprjs has the numbers and the question marks of the
+// cells of the whole rectangle only.
+fn check_encodable(puzzle: &Puzzle) -> EncodePzprResult<()> {
+    if puzzle.points().any(|p| puzzle.is_masked(p)) {
+        return Err(EncodePzprError::masked());
+    }
+    for r in 0..(puzzle.row() + 1) {
+        for c in 0..(puzzle.column() + 1) {
+            if puzzle.vertex_hint(Point(r, c)).is_some() {
+                return Err(EncodePzprError::vertex_hint());
+            }
+        }
+    }
+    if puzzle.points().any(|p| puzzle.marker(p).is_some()) {
+        return Err(EncodePzprError::marker());
+    }
+    if puzzle.points().any(|p| puzzle.hint_set(p).map_or(false, |set| set != HintSet::any())) {
+        return Err(EncodePzprError::hint_set());
+    }
+    if puzzle.loop_length().is_some() || puzzle.area().is_some() {
+        return Err(EncodePzprError::constraint());
+    }
+    Ok(())
+}
+
+fn parse_size(s: Option<&str>) -> ParsePzprResult<i32> {
+    match s.and_then(|s| s.parse::<i32>().ok()) {
+        Some(n) if n > 0 => Ok(n),
+        _ => Err(ParsePzprError::invalid_size()),
+    }
+}
+
+fn cell_point(puzzle: &Puzzle, i: usize) -> Point {
+    let col = puzzle.column() as usize;
+    Point((i / col) as i32, (i % col) as i32)
+}
+
+fn decode_hint(puzzle: &mut Puzzle, body: &str) -> ParsePzprResult<()> {
+    use self::ParsePzprError as Error;
+
+    let len = (puzzle.row() * puzzle.column()) as usize;
+    let mut i = 0;
+    for c in body.chars() {
+        if i >= len {
+            break;
+        }
+        let n = match c.to_digit(36) {
+            Some(n) => n as usize,
+            None if c == '.' => {
+                let p = cell_point(puzzle, i);
+                puzzle.set_hint_set(p, Some(HintSet::any()));
+                i += 1;
+                continue;
+            }
+            None => return Err(Error::invalid_data()),
+        };
+        match n {
+            // hint followed by 0, 1 or 2 empty cells
+            0...14 => {
+                let p = cell_point(puzzle, i);
+                puzzle.set_hint(p, Some((n % 5) as u8));
+                i += n / 5 + 1;
+            }
+            // run of 1 to 20 empty cells
+            16...35 => i += n - 15,
+            _ => return Err(Error::invalid_data()),
+        }
+    }
+    Ok(())
+}
+
+fn encode_hint(puzzle: &Puzzle) -> String {
+    let len = (puzzle.row() * puzzle.column()) as usize;
+    let hint = |i: usize| {
+        if i < len {
+            let p = cell_point(puzzle, i);
+            (puzzle.hint(p), puzzle.hint_set(p) == Some(HintSet::any()))
+        } else {
+            (None, false)
+        }
+    };
+    let is_empty = |i: usize| hint(i) == (None, false);
+
+    let mut s = String::new();
+    let mut count = 0;
+    let mut i = 0;
+    while i < len {
+        if !is_empty(i) && count > 0 {
+            s.push(radix36(15 + count));
+            count = 0;
+        }
+        match hint(i) {
+            (Some(n), _) => {
+                let skip = if !is_empty(i + 1) {
+                    0
+                } else if !is_empty(i + 2) {
+                    1
+                } else {
+                    2
+                };
+                s.push(radix36(5 * skip + n as usize));
+                i += skip + 1;
+            }
+            (None, true) => {
+                s.push('.');
+                i += 1;
+            }
+            (None, false) => {
+                count += 1;
+                if count == 20 {
+                    s.push(radix36(15 + count));
+                    count = 0;
+                }
+                i += 1;
+            }
+        }
+    }
+    if count > 0 {
+        s.push(radix36(15 + count));
+    }
+    s
+}
+
+fn radix36(n: usize) -> char {
+    ::std::char::from_digit(n as u32, 36).unwrap()
+}
+
+/// Returns true if the string looks like a pzprv3 file.
+pub fn is_file(s: &str) -> bool {
+    s.trim_left().starts_with(FILE_HEADER)
+}
+
+/// Parses a pzprv3 file into puzzle.
+///
+/// The line and cross marks of the edges are also read if the file contains
+/// them, so partially solved boards can be loaded.
+pub fn parse_file(s: &str) -> ParsePzprResult<Puzzle> {
+    use self::ParsePzprError as Error;
+
+    let mut lines = s.lines()
+                     .map(|l| l.trim())
+                     .skip_while(|l| l.is_empty())
+                     .peekable();
+
+    match lines.next() {
+        Some(l) if l.starts_with(FILE_HEADER) => {}
+        _ => return Err(Error::invalid_header()),
+    }
+    if lines.next() != Some(PUZZLE_TYPE) {
+        return Err(Error::unsupported_type());
+    }
+
+    let rows = try!(parse_size(lines.next()));
+    let cols = try!(parse_size(lines.next()));
+    let mut puzzle = Puzzle::new(Size(rows, cols));
+
+    for r in 0..rows {
+        for (c, s) in try!(file_row(lines.next(), cols)).into_iter().enumerate() {
+            let hint = match s {
+                "." => None,
+                "-" => {
+                    puzzle.set_hint_set(Point(r, c as i32), Some(HintSet::any()));
+                    None
+                }
+                _ => {
+                    match s.parse::<u8>() {
+                        Ok(n) if n <= 4 => Some(n),
+                        _ => return Err(Error::invalid_data()),
+                    }
+                }
+            };
+            puzzle.set_hint(Point(r, c as i32), hint);
+        }
+    }
+
+    // Edges may be omitted.
+    if lines.peek().map_or(true, |l| l.is_empty()) {
+        return Ok(puzzle);
+    }
+
+    for r in 0..rows {
+        for (c, s) in try!(file_row(lines.next(), cols + 1)).into_iter().enumerate() {
+            puzzle.set_edge_v(Point(r, c as i32), try!(parse_file_edge(s)));
+        }
+    }
+    for r in 0..(rows + 1) {
+        for (c, s) in try!(file_row(lines.next(), cols)).into_iter().enumerate() {
+            puzzle.set_edge_h(Point(r, c as i32), try!(parse_file_edge(s)));
+        }
+    }
+
+    Ok(puzzle)
+}
+
+/// Encodes the puzzle into pzprv3 file, including the state of the edges.
+pub fn to_file(puzzle: &Puzzle) -> EncodePzprResult<String> {
+    try!(check_encodable(puzzle));
+
+    let mut s = format!("{}\n{}\n{}\n{}\n",
+                        FILE_HEADER,
+                        PUZZLE_TYPE,
+                        puzzle.row(),
+                        puzzle.column());
+
+    for r in 0..puzzle.row() {
+        for c in 0..puzzle.column() {
+            let p = Point(r, c);
+            match puzzle.hint(p) {
+                Some(n) => s.push_str(&format!("{} ", n)),
+                None if puzzle.hint_set(p) == Some(HintSet::any()) => s.push_str("- "),
+                None => s.push_str(". "),
+            }
+        }
+        s.push('\n');
+    }
+    for r in 0..puzzle.row() {
+        for c in 0..(puzzle.column() + 1) {
+            s.push_str(file_edge(puzzle.edge_v(Point(r, c))));
+        }
+        s.push('\n');
+    }
+    for r in 0..(puzzle.row() + 1) {
+        for c in 0..puzzle.column() {
+            s.push_str(file_edge(puzzle.edge_h(Point(r, c))));
+        }
+        s.push('\n');
+    }
+
+    Ok(s)
+}
+
+fn file_row(line: Option<&str>, len: i32) -> ParsePzprResult<Vec<&str>> {
+    let row = line.map_or(vec![], |l| l.split_whitespace().collect::<Vec<_>>());
+    if row.len() != len as usize {
+        return Err(ParsePzprError::invalid_data());
+    }
+    Ok(row)
+}
+
+fn parse_file_edge(s: &str) -> ParsePzprResult<Option<Edge>> {
+    match s {
+        "0" => Ok(None),
+        "1" => Ok(Some(Edge::Line)),
+        "-1" => Ok(Some(Edge::Cross)),
+        _ => Err(ParsePzprError::invalid_data()),
+    }
+}
+
+fn file_edge(edge: Option<Edge>) -> &'static str {
+    match edge {
+        Some(Edge::Line) => "1 ",
+        Some(Edge::Cross) => "-1 ",
+        None => "0 ",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+    use super::{EncodePzprError, ParsePzprError, ParsePzprResult};
+    use geom::{Geom, Point, Size};
+    use puzzle::{Edge, HintSet, Marker, Puzzle};
+
+    fn check_error<T>(result: ParsePzprResult<T>, error: ParsePzprError) {
+        match result {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => assert_eq!(e.description(), error.description()),
+        }
+    }
+
+    #[test]
+    fn parse_url() {
+        let puzzle = super::parse_url("https://puzz.link/p?slither/4/3/8ag6ag").unwrap();
+        assert_eq!(Size(3, 4), puzzle.size());
+        assert_eq!(Some(3), puzzle.hint(Point(0, 0)));
+        assert_eq!(None, puzzle.hint(Point(0, 1)));
+        assert_eq!(Some(0), puzzle.hint(Point(0, 2)));
+        assert_eq!(None, puzzle.hint(Point(0, 3)));
+        assert_eq!(None, puzzle.hint(Point(1, 0)));
+        assert_eq!(None, puzzle.hint(Point(1, 1)));
+        assert_eq!(Some(1), puzzle.hint(Point(1, 2)));
+        assert_eq!(None, puzzle.hint(Point(1, 3)));
+        assert_eq!(Some(0), puzzle.hint(Point(2, 0)));
+        assert_eq!(None, puzzle.hint(Point(2, 1)));
+        assert_eq!(None, puzzle.hint(Point(2, 2)));
+        assert_eq!(None, puzzle.hint(Point(2, 3)));
+
+        assert_eq!(puzzle, super::parse_url("slither/4/3/3g5h1g0h").unwrap());
+        assert_eq!("https://puzz.link/p?slither/4/3/8ag6ag",
+                   super::to_url(&puzzle).unwrap());
+    }
+
+    #[test]
+    fn roundtrip() {
+        let input = "___113__
+2__1___2
+_3__1_33
+2113221_
+3_11_2__
+301222_2
+2_012___
+02_22223
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        let url = super::to_url(&puzzle).unwrap();
+        assert_eq!("https://puzz.link/p?slither/8/8/i11dcbg7d6332113226816c3012272701cg072222d",
+                   url);
+        assert!(super::is_url(&url));
+        assert_eq!(puzzle, super::parse_url(&url).unwrap());
+
+        let empty = Puzzle::new(Size(5, 5));
+        assert_eq!("https://puzz.link/p?slither/5/5/zk", super::to_url(&empty).unwrap());
+        assert_eq!(empty, super::parse_url(&super::to_url(&empty).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn question_mark() {
+        let puzzle = super::parse_url("?slither/4/1/.h.").unwrap();
+        assert_eq!(Some(HintSet::any()), puzzle.hint_set(Point(0, 0)));
+        assert_eq!(None, puzzle.hint(Point(0, 0)));
+        assert_eq!(None, puzzle.hint_set(Point(0, 1)));
+        assert_eq!(Some(HintSet::any()), puzzle.hint_set(Point(0, 3)));
+        assert_eq!("https://puzz.link/p?slither/4/1/.h.",
+                   super::to_url(&puzzle).unwrap());
+
+        let input = "pzprv3\nslither\n1\n2\n- 1 \n";
+        let puzzle = super::parse_file(input).unwrap();
+        assert_eq!(Some(HintSet::any()), puzzle.hint_set(Point(0, 0)));
+        assert_eq!(Some(1), puzzle.hint(Point(0, 1)));
+        assert!(super::to_file(&puzzle).unwrap().starts_with(input));
+    }
+
+    #[test]
+    fn parse_file() {
+        let input = "pzprv3
+slither
+2
+3
+3 . . 
+. 1 . 
+1 1 0 0 
+-1 0 0 -1 
+1 0 0 
+-1 0 0 
+0 -1 0 
+";
+        let puzzle = super::parse_file(input).unwrap();
+        assert_eq!(Size(2, 3), puzzle.size());
+        assert_eq!(Some(3), puzzle.hint(Point(0, 0)));
+        assert_eq!(None, puzzle.hint(Point(0, 1)));
+        assert_eq!(Some(1), puzzle.hint(Point(1, 1)));
+        assert_eq!(Some(Edge::Line), puzzle.edge_v(Point(0, 0)));
+        assert_eq!(Some(Edge::Line), puzzle.edge_v(Point(0, 1)));
+        assert_eq!(None, puzzle.edge_v(Point(0, 2)));
+        assert_eq!(Some(Edge::Cross), puzzle.edge_v(Point(1, 0)));
+        assert_eq!(Some(Edge::Cross), puzzle.edge_v(Point(1, 3)));
+        assert_eq!(Some(Edge::Line), puzzle.edge_h(Point(0, 0)));
+        assert_eq!(Some(Edge::Cross), puzzle.edge_h(Point(1, 0)));
+        assert_eq!(Some(Edge::Cross), puzzle.edge_h(Point(2, 1)));
+        assert_eq!(None, puzzle.edge_h(Point(2, 2)));
+        assert_eq!(input, super::to_file(&puzzle).unwrap());
+
+        let puzzle = super::parse_file("pzprv3\nslither\n1\n2\n2 .\n").unwrap();
+        assert_eq!(Some(2), puzzle.hint(Point(0, 0)));
+        assert_eq!(None, puzzle.edge_h(Point(0, 0)));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        check_error(super::parse_url("?nurikabe/3/3/"),
+                    ParsePzprError::unsupported_type());
+        check_error(super::parse_url("?slither/0/3/"),
+                    ParsePzprError::invalid_size());
+        check_error(super::parse_url("?slither/3"), ParsePzprError::invalid_size());
+        check_error(super::parse_url("?slither/3/3/1f"),
+                    ParsePzprError::invalid_data());
+        check_error(super::parse_url("?slither/3/3/1!"),
+                    ParsePzprError::invalid_data());
+
+        check_error(super::parse_file("slither\n1\n1\n."),
+                    ParsePzprError::invalid_header());
+        check_error(super::parse_file("pzprv3\nnurikabe\n1\n1\n."),
+                    ParsePzprError::unsupported_type());
+        check_error(super::parse_file("pzprv3\nslither\n1\n2\n. . .\n"),
+                    ParsePzprError::invalid_data());
+        check_error(super::parse_file("pzprv3\nslither\n1\n1\n5\n"),
+                    ParsePzprError::invalid_data());
+        check_error(super::parse_file("pzprv3\nslither\n1\n1\n.\n0 2\n"),
+                    ParsePzprError::invalid_data());
+    }
+
+    #[test]
+    fn encode_invalid() {
+        let mut puzzle = super::parse_url("?slither/2/1/8").unwrap();
+        puzzle.set_vertex_hint(Point(1, 2), Some(0));
+        assert_eq!(EncodePzprError::vertex_hint().description(),
+                   super::to_url(&puzzle).unwrap_err().description());
+        assert_eq!(EncodePzprError::vertex_hint().description(),
+                   super::to_file(&puzzle).unwrap_err().description());
+
+        let mut puzzle = super::parse_url("?slither/2/1/8").unwrap();
+        puzzle.set_marker(Point(0, 1), Some(Marker::Sheep));
+        assert_eq!(EncodePzprError::marker().description(),
+                   super::to_url(&puzzle).unwrap_err().description());
+
+        let mut puzzle = super::parse_url("?slither/2/1/8").unwrap();
+        puzzle.set_hint_set(Point(0, 1), Some(HintSet::new(&[1, 2])));
+        assert_eq!(EncodePzprError::hint_set().description(),
+                   super::to_url(&puzzle).unwrap_err().description());
+        assert_eq!(EncodePzprError::hint_set().description(),
+                   super::to_file(&puzzle).unwrap_err().description());
+
+        let mut puzzle = super::parse_url("?slither/2/1/8").unwrap();
+        puzzle.set_masked(Point(0, 1), true);
+        assert_eq!(EncodePzprError::masked().description(),
+                   super::to_url(&puzzle).unwrap_err().description());
+        assert_eq!(EncodePzprError::masked().description(),
+                   super::to_file(&puzzle).unwrap_err().description());
+
+        let mut puzzle = super::parse_url("?slither/2/1/8").unwrap();
+        puzzle.set_loop_length(Some(4));
+        assert_eq!(EncodePzprError::constraint().description(),
+                   super::to_url(&puzzle).unwrap_err().description());
+        let mut puzzle = super::parse_url("?slither/2/1/8").unwrap();
+        puzzle.set_area(Some(1));
+        assert_eq!(EncodePzprError::constraint().description(),
+                   super::to_file(&puzzle).unwrap_err().description());
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright (c) 2016 srither-core developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Drawing puzzles as TikZ pictures for LaTeX documents.
+//!
+//! The picture uses the cell coordinates as is: `(c, r)` is the top-left
+//! corner of the cell at row `r` and column `c`. The `dot`, `hint`,
+//! `vertex hint` and `loop line` styles are defined before the picture only
+//! if they are not defined yet, so the enclosing document can redefine them
+//! with `\tikzset` in its preamble. The hints of the vertices are drawn in
+//! place of their dots, over the lines, and the sheep and the wolves are
+//! drawn as `S` and `W` in the `hint` style, as well as the sets of the
+//! numbers such as `12` or `?`.
+
+use std::fmt;
+
+use geom::{Geom, Point};
+use puzzle::{Edge, Marker, Puzzle};
+
+/// What to draw in the picture.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// Draws only the dots and the hints.
+    Bare,
+    /// Draws the lines of the puzzle in addition to the hints.
+    Solved,
+}
+
+/// A wrapper to format the puzzle as a TikZ picture with `Display`.
+#[derive(Copy, Clone, Debug)]
+pub struct Tikz<'a> {
+    puzzle: &'a Puzzle,
+    mode: Mode,
+}
+
+impl<'a> Tikz<'a> {
+    /// Creates a TikZ picture of the puzzle.
+    pub fn new(puzzle: &'a Puzzle, mode: Mode) -> Tikz<'a> {
+        Tikz {
+            puzzle: puzzle,
+            mode: mode,
+        }
+    }
+}
+
+// The default styles, which the document may have defined already.
+const STYLES: [(&'static str, &'static str); 4] =
+    [("dot", "black"),
+     ("hint", "font=\\large"),
+     ("vertex hint", "circle, draw, fill=white, inner sep=1pt, font=\\small"),
+     ("loop line", "line width=2pt, line cap=round")];
+
+// The dots which are not a corner of any cell on the board are not drawn,
+// and the dots with hints are drawn by `VertexHint`.
+struct Dot<'a>(&'a Puzzle, Point);
+impl<'a> fmt::Display for Dot<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Dot(puzzle, p) = *self;
+        if !puzzle.is_drawn_vertex(p) || puzzle.vertex_hint(p).is_some() {
+            return Ok(());
+        }
+        writeln!(f, "  \\fill[dot] ({},{}) circle (0.06);", p.1, p.0)
+    }
+}
+
+struct VertexHint<'a>(&'a Puzzle, Point);
+impl<'a> fmt::Display for VertexHint<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let VertexHint(puzzle, p) = *self;
+        match puzzle.vertex_hint(p) {
+            Some(n) => writeln!(f, "  \\node[vertex hint] at ({},{}) {{{}}};", p.1, p.0, n),
+            None => Ok(()),
+        }
+    }
+}
+
+struct HEdge<'a>(&'a Puzzle, Point);
+impl<'a> fmt::Display for HEdge<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let HEdge(puzzle, p) = *self;
+        if puzzle.edge_h(p) == Some(Edge::Line) {
+            try!(writeln!(f, "  \\draw[loop line] ({},{}) -- ++(1,0);", p.1, p.0));
+        }
+        Ok(())
+    }
+}
+
+struct VEdge<'a>(&'a Puzzle, Point);
+impl<'a> fmt::Display for VEdge<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let VEdge(puzzle, p) = *self;
+        if puzzle.edge_v(p) == Some(Edge::Line) {
+            try!(writeln!(f, "  \\draw[loop line] ({},{}) -- ++(0,1);", p.1, p.0));
+        }
+        Ok(())
+    }
+}
+
+struct EdgeRow<'a>(&'a Puzzle, Mode, i32);
+impl<'a> fmt::Display for EdgeRow<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let EdgeRow(puzzle, mode, r) = *self;
+        for c in 0..puzzle.column() {
+            let p = Point(r, c);
+            try!(write!(f, "{}", Dot(puzzle, p)));
+            if mode == Mode::Solved {
+                try!(write!(f, "{}", HEdge(puzzle, p)));
+            }
+        }
+        try!(write!(f, "{}", Dot(puzzle, Point(r, puzzle.column()))));
+        Ok(())
+    }
+}
+
+struct CellRow<'a>(&'a Puzzle, Mode, i32);
+impl<'a> fmt::Display for CellRow<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let CellRow(puzzle, mode, r) = *self;
+        for c in 0..puzzle.column() {
+            let p = Point(r, c);
+            if mode == Mode::Solved {
+                try!(write!(f, "{}", VEdge(puzzle, p)));
+            }
+            let text = match (puzzle.hint(p), puzzle.hint_set(p), puzzle.marker(p)) {
+                (Some(n), _, _) => n.to_string(),
+                (None, Some(set), _) => set.to_string(),
+                (None, None, Some(Marker::Sheep)) => "S".to_owned(),
+                (None, None, Some(Marker::Wolf)) => "W".to_owned(),
+                (None, None, None) => continue,
+            };
+            try!(writeln!(f, "  \\node[hint] at ({}.5,{}.5) {{{}}};", c, r, text));
+        }
+        if mode == Mode::Solved {
+            try!(write!(f, "{}", VEdge(puzzle, Point(r, puzzle.column()))));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for Tikz<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Tikz { puzzle, mode } = *self;
+        for &(name, style) in &STYLES {
+            try!(writeln!(f,
+                          "\\pgfkeysifdefined{{/tikz/{0}/.@cmd}}{{}}{{\\tikzset{{{0}/.style={{{1}}}}}}}",
+                          name,
+                          style));
+        }
+        try!(writeln!(f, "\\begin{{tikzpicture}}[x=0.8cm, y=-0.8cm]"));
+        for r in 0..puzzle.row() {
+            try!(write!(f, "{}", EdgeRow(puzzle, mode, r)));
+            try!(write!(f, "{}", CellRow(puzzle, mode, r)));
+        }
+        try!(write!(f, "{}", EdgeRow(puzzle, mode, puzzle.row())));
+        for r in 0..(puzzle.row() + 1) {
+            for c in 0..(puzzle.column() + 1) {
+                try!(write!(f, "{}", VertexHint(puzzle, Point(r, c))));
+            }
+        }
+        try!(writeln!(f, "\\end{{tikzpicture}}"));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mode, Tikz};
+    use puzzle::Puzzle;
+
+    #[test]
+    fn bare_and_solved() {
+        let input = "+-+
+|3|
++-+
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        let header = "\\pgfkeysifdefined{/tikz/dot/.@cmd}{}{\\tikzset{dot/.style={black}}}
+\\pgfkeysifdefined{/tikz/hint/.@cmd}{}{\\tikzset{hint/.style={font=\\large}}}
+\\pgfkeysifdefined{/tikz/vertex hint/.@cmd}{}{\\tikzset{vertex hint/.style={circle, draw, \
+                      fill=white, inner sep=1pt, font=\\small}}}
+\\pgfkeysifdefined{/tikz/loop line/.@cmd}{}{\\tikzset{loop line/.style={line width=2pt, line \
+                      cap=round}}}
+\\begin{tikzpicture}[x=0.8cm, y=-0.8cm]
+";
+
+        let expected = format!("{}  \\fill[dot] (0,0) circle (0.06);
+  \\fill[dot] (1,0) circle (0.06);
+  \\node[hint] at (0.5,0.5) {{3}};
+  \\fill[dot] (0,1) circle (0.06);
+  \\fill[dot] (1,1) circle (0.06);
+\\end{{tikzpicture}}
+",
+                               header);
+        assert_eq!(expected, Tikz::new(&puzzle, Mode::Bare).to_string());
+
+        let expected = format!("{}  \\fill[dot] (0,0) circle (0.06);
+  \\draw[loop line] (0,0) -- ++(1,0);
+  \\fill[dot] (1,0) circle (0.06);
+  \\draw[loop line] (0,0) -- ++(0,1);
+  \\node[hint] at (0.5,0.5) {{3}};
+  \\draw[loop line] (1,0) -- ++(0,1);
+  \\fill[dot] (0,1) circle (0.06);
+  \\draw[loop line] (0,1) -- ++(1,0);
+  \\fill[dot] (1,1) circle (0.06);
+\\end{{tikzpicture}}
+",
+                               header);
+        assert_eq!(expected, Tikz::new(&puzzle, Mode::Solved).to_string());
+    }
+
+    #[test]
+    fn vertex_hint() {
+        let input = "2-+
+|3|
++-+
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        let tikz = Tikz::new(&puzzle, Mode::Solved).to_string();
+        assert!(!tikz.contains("\\fill[dot] (0,0)"));
+        assert!(tikz.contains("\\fill[dot] (1,0)"));
+        assert!(tikz.ends_with("  \\node[vertex hint] at (0,0) {2};\n\\end{tikzpicture}\n"));
+    }
+}
@@ -33,9 +33,11 @@ extern crate srither_core;
 
 use std::{fmt, mem};
 use std::error::Error as ErrorTrait;
+use std::rc::Rc;
 
-use srither_core::puzzle::Puzzle;
-use srither_core::geom::CellId;
+use srither_core::graph::Board;
+use srither_core::puzzle::{Edge, Puzzle, Side};
+use srither_core::geom::{CellId, Geom, Point};
 
 use model::State;
 use solver::Solver;
@@ -45,6 +47,9 @@ mod model;
 
 mod step {
     pub mod connect_analysis;
+    pub mod hint_set;
+    pub mod loop_constraint;
+    pub mod vertex_hint;
 }
 mod theorem_define;
 mod solver;
@@ -83,9 +88,9 @@ impl Error {
 /// Solving puzzles result.
 pub type SolverResult<T> = Result<T, Error>;
 
-enum FillResult<'a> {
-    Completed(Solver<'a>),
-    Partial(Solver<'a>, Vec<CellId>),
+enum FillResult {
+    Completed(Solver),
+    Partial(Solver, Vec<CellId>),
 }
 
 fn fill_absolutely_fixed(solver: &mut Solver) -> SolverResult<()> {
@@ -97,6 +102,21 @@ fn fill_absolutely_fixed(solver: &mut Solver) -> SolverResult<()> {
             continue;
         }
 
+        try!(solver.apply_hint_sets());
+        if solver.revision() != rev {
+            continue;
+        }
+
+        try!(solver.apply_vertex_hints());
+        if solver.revision() != rev {
+            continue;
+        }
+
+        try!(solver.apply_loop_constraints());
+        if solver.revision() != rev {
+            continue;
+        }
+
         try!(solver.connect_analysis());
         if solver.revision() != rev {
             continue;
@@ -165,24 +185,25 @@ fn fill(mut solver: Solver) -> SolverResult<FillResult> {
     Ok(FillResult::Partial(solver, pts))
 }
 
-/// An iterator iterates all solutions of the puzzle.
+/// An iterator iterates all solutions of the board.
 #[derive(Clone, Debug)]
-pub struct Solutions<'a> {
-    queue: Vec<Solver<'a>>,
+pub struct BoardSolutions {
+    queue: Vec<Solver>,
 }
 
-impl<'a> Solutions<'a> {
-    /// Creates an solutions iterator of the puzzle.
-    pub fn new(puzzle: &'a Puzzle) -> SolverResult<Solutions<'a>> {
+impl BoardSolutions {
+    /// Creates an solutions iterator of the board.
+    pub fn new(board: &Board) -> SolverResult<BoardSolutions> {
         let theorem = THEOREM_DEFINE.iter().map(|theo| theo.parse().unwrap());
-        Ok(Solutions { queue: vec![try!(Solver::new(puzzle, theorem))] })
+        let solver = try!(Solver::new(Rc::new(board.clone()), theorem));
+        Ok(BoardSolutions { queue: vec![solver] })
     }
 }
 
-impl<'a> Iterator for Solutions<'a> {
-    type Item = Puzzle;
+impl Iterator for BoardSolutions {
+    type Item = Board;
 
-    fn next(&mut self) -> Option<Puzzle> {
+    fn next(&mut self) -> Option<Board> {
         while let Some(solver) = self.queue.pop() {
             let (solver, pts) = match fill(solver) {
                 Ok(FillResult::Completed(mut solver)) => {
@@ -210,6 +231,66 @@ impl<'a> Iterator for Solutions<'a> {
     }
 }
 
+/// An iterator iterates all solutions of the puzzle.
+#[derive(Clone, Debug)]
+pub struct Solutions<'a> {
+    puzzle: &'a Puzzle,
+    solutions: BoardSolutions,
+}
+
+impl<'a> Solutions<'a> {
+    /// Creates an solutions iterator of the puzzle.
+    pub fn new(puzzle: &'a Puzzle) -> SolverResult<Solutions<'a>> {
+        try!(check_puzzle(puzzle));
+        let board = Board::from_puzzle(puzzle);
+        Ok(Solutions {
+            puzzle: puzzle,
+            solutions: try!(BoardSolutions::new(&board)),
+        })
+    }
+}
+
+impl<'a> Iterator for Solutions<'a> {
+    type Item = Puzzle;
+
+    fn next(&mut self) -> Option<Puzzle> {
+        self.solutions.next().map(|board| {
+            let mut puzzle = self.puzzle.clone();
+            board.fill_puzzle(&mut puzzle);
+            puzzle
+        })
+    }
+}
+
+// The masked cells are not the faces of the board, so the constraints on
+// them would be dropped silently.
+fn check_puzzle(puzzle: &Puzzle) -> SolverResult<()> {
+    for p in puzzle.points() {
+        if puzzle.is_masked(p) &&
+           (puzzle.hint(p).is_some() || puzzle.hint_set(p).is_some() ||
+            puzzle.marker(p).is_some() || puzzle.side(p) == Some(Side::In)) {
+            return Err(Error::invalid_board());
+        }
+    }
+    for r in 0..(puzzle.row() + 1) {
+        for c in 0..(puzzle.column() + 1) {
+            let p = Point(r, c);
+            if !puzzle.is_drawn_vertex(p) && puzzle.vertex_hint(p).map_or(false, |n| n > 0) {
+                return Err(Error::invalid_board());
+            }
+            if c < puzzle.column() && !puzzle.is_drawn_edge_h(p) &&
+               puzzle.edge_h(p) == Some(Edge::Line) {
+                return Err(Error::invalid_board());
+            }
+            if r < puzzle.row() && !puzzle.is_drawn_edge_v(p) &&
+               puzzle.edge_v(p) == Some(Edge::Line) {
+                return Err(Error::invalid_board());
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Returns the first solution of the puzzle.
 pub fn solve(puzzle: &Puzzle) -> SolverResult<Puzzle> {
     let mut it = try!(Solutions::new(puzzle));
@@ -219,3 +300,205 @@ pub fn solve(puzzle: &Puzzle) -> SolverResult<Puzzle> {
 
     Err(Error::invalid_board())
 }
+
+/// Returns the first solution of the board.
+pub fn solve_board(board: &Board) -> SolverResult<Board> {
+    let mut it = try!(BoardSolutions::new(board));
+    if let Some(solution) = it.next() {
+        return Ok(solution);
+    }
+
+    Err(Error::invalid_board())
+}
+
+#[cfg(test)]
+mod tests {
+    use srither_core::graph::Board;
+    use srither_core::puzzle::{Edge, Puzzle};
+    use super::{BoardSolutions, Solutions};
+
+    fn check(input: &str, expected: &str) {
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        let solutions = Solutions::new(&puzzle).unwrap().collect::<Vec<_>>();
+        assert_eq!(1, solutions.len());
+        assert_eq!(expected, solutions[0].to_string());
+    }
+
+    #[test]
+    fn irregular_board() {
+        let input = "#3_
+3_3
+_3#
+";
+        let expected = "+xx+--+xx+
+x#o|3i| ox
++--+xx+--+
+|3ix ix3i|
++--+xx+--+
+x o|3i|#ox
++xx+--+xx+
+";
+        check(input, expected);
+    }
+
+    #[test]
+    fn vertex_hints() {
+        let input = "+ + + +
+
+2 + + +
+
++ 0 + 2
+
+0 + + 2
+";
+        let expected = "+--+--+--+
+| ix ix i|
+2--+--+xx+
+x ox o| i|
++xx0xx+xx2
+x ox o| i|
+0xx+xx+--2
+";
+        check(input, expected);
+    }
+
+    #[test]
+    fn vertex_and_cell_hints() {
+        // The hints of the cells only have three solutions.
+        let input = "+ + + +
+   1
++ + + +
+ 3   1
++ + + +
+
++ + + +
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(3, Solutions::new(&puzzle).unwrap().count());
+
+        let input = "+ + + +
+   1
++ + 0 +
+ 3   1
++ + + +
+
++ + + +
+";
+        let expected = "+--+--+--+
+| ix1ix i|
++--+xx0xx+
+x3o| ix1i|
++--+xx+xx+
+| ix ix i|
++--+--+--+
+";
+        check(input, expected);
+    }
+
+    #[test]
+    fn sheep_and_wolves() {
+        // The hints only have four solutions.
+        let puzzle = "_3_\n__0\n___\n".parse::<Puzzle>().unwrap();
+        assert_eq!(4, Solutions::new(&puzzle).unwrap().count());
+
+        let input = "_3_
+__0
+ws_
+";
+        let expected = "+--+--+xx+
+| ix3i| ox
++xx+--+xx+
+| i| ox0ox
++xx+xx+xx+
+|wi|sox ox
++--+xx+xx+
+";
+        check(input, expected);
+
+        // The sheep and the wolf cannot be on the same side of a cross.
+        let puzzle = "+ + +\n sxw\n+ + +\n".parse::<Puzzle>().unwrap();
+        assert!(Solutions::new(&puzzle).is_err());
+    }
+
+    #[test]
+    fn hint_sets() {
+        // `?` may be 0, so the loop may go around the bottom-left cell only.
+        let input = "+  +  +
+ 1  ?
++  +  +
+
++  +  +
+";
+        let puzzle = input.parse::<Puzzle>().unwrap();
+        assert_eq!(2, Solutions::new(&puzzle).unwrap().count());
+
+        // The set does not have 0, so the loop cannot be closed around the
+        // bottom-left cell.
+        let input = "+  +  +
+ 1  12
++  +  +
+
++  +  +
+";
+        let expected = "+xxx+xxx+
+x1 ox12ox
++---+---+
+|  ix  i|
++---+---+
+";
+        check(input, expected);
+    }
+
+    #[test]
+    fn loop_length_and_area() {
+        // The hints only have three solutions, and two of them have loops of
+        // length 14 around 6 cells.
+        let hints = "___\n__3\n31_\n";
+        let puzzle = hints.parse::<Puzzle>().unwrap();
+        assert_eq!(3, Solutions::new(&puzzle).unwrap().count());
+
+        let solution = "+--+--+--+
+| ix ix i|
++--+xx+--+
+x o| i|3ox
++--+xx+--+
+|3ix1ix i|
++--+--+--+
+";
+        check(&format!("length: 16\n{}", hints),
+              &format!("length: 16\n{}", solution));
+        check(&format!("area: 7\n{}", hints),
+              &format!("area: 7\n{}", solution));
+
+        // A loop on the square grid cannot have an odd length.
+        let puzzle = format!("length: 15\n{}", hints).parse::<Puzzle>().unwrap();
+        assert!(super::solve(&puzzle).is_err());
+        let puzzle = format!("area: 5\n{}", hints).parse::<Puzzle>().unwrap();
+        assert!(super::solve(&puzzle).is_err());
+    }
+
+    fn check_board(input: &str) {
+        let board = input.parse::<Board>().unwrap();
+        let solutions = BoardSolutions::new(&board).unwrap().collect::<Vec<_>>();
+        assert_eq!(1, solutions.len());
+        let lines = (0..board.num_edges())
+                        .map(|e| solutions[0].edge(e) == Some(Edge::Line))
+                        .collect::<Vec<_>>();
+        for f in 0..board.num_faces() {
+            if let Some(n) = board.hint(f) {
+                let count = board.face_edges(f).iter().filter(|&&e| lines[e]).count();
+                assert_eq!(n as usize, count);
+            }
+        }
+        assert!(board.loop_path(&lines).is_some());
+    }
+
+    #[test]
+    fn tilings() {
+        check_board("square\n33\n__\n");
+        check_board("hexagonal\n55\n__\n");
+        check_board("triangular\n222\n___\n");
+        check_board("cairo\n_3_2\n13__\n");
+        assert!(super::solve_board(&"square\n0\n".parse().unwrap()).is_err());
+    }
+}
@@ -9,11 +9,12 @@
 use std::iter::FromIterator;
 use std::mem;
 use union_find::{QuickFindUf as Uf, Union, UnionFind, UnionResult};
-use srither_core::puzzle::{Edge, Puzzle, Side};
-use srither_core::geom::{CellId, Geom, Move, Point};
+use srither_core::graph::{Board, Id};
+use srither_core::puzzle::{Edge, Side};
+use srither_core::geom::CellId;
 
 use {Error, SolverResult};
-use model::State;
+use model::{self, State};
 use model::side_map::SideMap;
 
 #[derive(Debug)]
@@ -60,29 +61,52 @@ impl Area {
     }
 }
 
+// The edges around the cell. The outside is around the edges on the border
+// of the board.
+fn cell_edges(c: CellId, board: &Board) -> Vec<Id> {
+    if c.is_outside() {
+        (0..board.num_edges())
+            .filter(|&e| model::edge_cells(board, e).1.is_outside())
+            .collect()
+    } else {
+        board.face_edges(c.id() - 1).to_vec()
+    }
+}
+
 impl Area {
-    fn new(p: Point, puzzle: &Puzzle, side_map: &mut SideMap) -> Area {
-        let cp = puzzle.point_to_cellid(p);
-        let sum = puzzle.hint(p).unwrap_or(0) as u32;
+    fn new(cp: CellId, board: &Board, side_map: &mut SideMap) -> Area {
+        let edges = cell_edges(cp, board);
 
-        let mut edge = vec![];
-        if !cp.is_outside() {
-            for &r in &Move::ALL_DIRECTIONS {
-                let cp_r = puzzle.point_to_cellid(p + r);
-                if side_map.get_edge(cp, cp_r) == State::Unknown {
-                    edge.push(cp_r);
-                }
-            }
+        // A set of the numbers counts its smallest one, which the loop
+        // passes around the cell at least.
+        let hint = if cp.is_outside() {
+            None
         } else {
-            let points = puzzle.points_in_column(0)
-                               .chain(puzzle.points_in_column(puzzle.column() - 1))
-                               .chain(puzzle.points_in_row(0))
-                               .chain(puzzle.points_in_row(puzzle.row() - 1));
-            for p2 in points {
-                let cp2 = puzzle.point_to_cellid(p2);
-                if side_map.get_edge(cp, cp2) == State::Unknown {
-                    edge.push(cp2);
-                }
+            let f = cp.id() - 1;
+            board.hint(f).or_else(|| board.hint_set(f).map(|s| s.min()))
+        };
+
+        // The hints of the vertices are counted in all the cells around
+        // them, as the loop touches some of the cells.
+        let mut vertices = edges.iter()
+                                .flat_map(|&e| {
+                                    let (v0, v1) = board.edge_vertices(e);
+                                    vec![v0, v1]
+                                })
+                                .collect::<Vec<_>>();
+        vertices.sort();
+        vertices.dedup();
+        let sum = hint.unwrap_or(0) as u32 +
+                  vertices.iter()
+                          .map(|&v| board.vertex_hint(v).unwrap_or(0) as u32)
+                          .sum::<u32>();
+
+        let mut edge = vec![];
+        for e in edges {
+            let (c0, c1) = model::edge_cells(board, e);
+            let cp2 = if c0 == cp { c1 } else { c0 };
+            if side_map.get_edge(cp, cp2) == State::Unknown {
+                edge.push(cp2);
             }
         }
         edge.sort();
@@ -163,13 +187,12 @@ impl Clone for ConnectMap {
 }
 
 impl ConnectMap {
-    pub fn new(puzzle: &Puzzle, side_map: &mut SideMap) -> ConnectMap {
-        let cell_len = puzzle.cell_len();
+    pub fn new(board: &Board, side_map: &mut SideMap) -> ConnectMap {
+        let cell_len = board.num_faces() + 1;
 
         let mut uf = Uf::from_iter((0..cell_len)
                                        .map(CellId::new)
-                                       .map(|id| puzzle.cellid_to_point(id))
-                                       .map(|p| Area::new(p, puzzle, side_map)));
+                                       .map(|c| Area::new(c, board, side_map)));
 
         let mut sum_of_hint = 0;
         for i in 0..cell_len {
@@ -182,14 +205,10 @@ impl ConnectMap {
             sync_revision: 0,
         };
 
-        for p in puzzle.points() {
-            let cp = puzzle.point_to_cellid(p);
-            for &r in &Move::ALL_DIRECTIONS {
-                let p2 = p + r;
-                let cp2 = puzzle.point_to_cellid(p2);
-                if side_map.get_edge(cp, cp2) == State::Fixed(Edge::Cross) {
-                    conn_map.union(cp, cp2);
-                }
+        for e in 0..board.num_edges() {
+            let (c0, c1) = model::edge_cells(board, e);
+            if side_map.get_edge(c0, c1) == State::Fixed(Edge::Cross) {
+                conn_map.union(c0, c1);
             }
         }
         conn_map
@@ -6,6 +6,9 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
+use srither_core::geom::CellId;
+use srither_core::graph::{Board, Id};
+
 use {Error, SolverResult};
 
 pub use self::connect_map::ConnectMap;
@@ -35,3 +38,17 @@ impl<T> Into<SolverResult<Option<T>>> for State<T> {
         }
     }
 }
+
+// The faces of the board are the cells after the outside cell.
+pub fn face_cell(f: Option<Id>) -> CellId {
+    match f {
+        Some(f) => CellId::new(f + 1),
+        None => CellId::OUTSIDE,
+    }
+}
+
+// The cells on both sides of the edge.
+pub fn edge_cells(board: &Board, e: Id) -> (CellId, CellId) {
+    let (f0, f1) = board.edge_faces(e);
+    (face_cell(f0), face_cell(f1))
+}
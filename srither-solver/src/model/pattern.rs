@@ -6,11 +6,12 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use srither_core::puzzle::{Edge, Puzzle};
-use srither_core::geom::{CellId, Geom, Move, Point, Rotation};
+use srither_core::graph::Board;
+use srither_core::puzzle::Edge;
+use srither_core::geom::{CellId, Move, Point, Rotation};
 
 use {Error, SolverResult};
-use model::{SideMap, State};
+use model::{self, SideMap, State};
 
 pub enum MatchResult<T> {
     Complete,
@@ -51,8 +52,10 @@ impl HintPattern {
         Self::new(self.hint, p + d)
     }
 
-    pub fn matches<T>(self, puzzle: &Puzzle) -> SolverResult<MatchResult<T>> {
-        if puzzle.hint(self.point) == Some(self.hint) {
+    // The cells with the sets of the numbers have no exact hint, so they
+    // never match.
+    pub fn matches<T>(self, board: &Board) -> SolverResult<MatchResult<T>> {
+        if board.face_at(self.point).and_then(|f| board.hint(f)) == Some(self.hint) {
             Ok(MatchResult::Complete)
         } else {
             Ok(MatchResult::Conflict)
@@ -67,19 +70,9 @@ pub struct EdgePattern<P> {
 }
 
 impl<P> EdgePattern<P> {
-    pub fn edge(&self) -> Edge {
-        self.edge
-    }
-
-    pub fn points(&self) -> (P, P)
-        where P: Copy
+    fn new(edge: Edge, p0: P, p1: P) -> EdgePattern<P>
+        where P: Ord
     {
-        self.points
-    }
-}
-
-impl EdgePattern<Point> {
-    fn new(edge: Edge, p0: Point, p1: Point) -> EdgePattern<Point> {
         let points = if p0 <= p1 {
             (p0, p1)
         } else {
@@ -92,14 +85,31 @@ impl EdgePattern<Point> {
         }
     }
 
-    pub fn cross(p0: Point, p1: Point) -> EdgePattern<Point> {
+    pub fn cross(p0: P, p1: P) -> EdgePattern<P>
+        where P: Ord
+    {
         EdgePattern::new(Edge::Cross, p0, p1)
     }
 
-    pub fn line(p0: Point, p1: Point) -> EdgePattern<Point> {
+    pub fn line(p0: P, p1: P) -> EdgePattern<P>
+        where P: Ord
+    {
         EdgePattern::new(Edge::Line, p0, p1)
     }
 
+    pub fn edge(&self) -> Edge {
+        self.edge
+    }
+
+    pub fn points(&self) -> (P, P)
+        where P: Copy
+    {
+        self.points
+    }
+}
+
+impl EdgePattern<Point> {
+
     pub fn rotate(self, rot: Rotation) -> EdgePattern<Point> {
         let o = Point(0, 0);
         let ps = self.points;
@@ -111,20 +121,18 @@ impl EdgePattern<Point> {
         Self::new(self.edge, ps.0 + d, ps.1 + d)
     }
 
-    pub fn to_cellid(self, puzzle: &Puzzle) -> EdgePattern<CellId> {
-        let p0 = puzzle.point_to_cellid(self.points.0);
-        let p1 = puzzle.point_to_cellid(self.points.1);
-        EdgePattern {
-            edge: self.edge,
-            points: (p0, p1),
-        }
+    // The points out of the board are the outside.
+    pub fn to_cellid(self, board: &Board) -> EdgePattern<CellId> {
+        let p0 = model::face_cell(board.face_at(self.points.0));
+        let p1 = model::face_cell(board.face_at(self.points.1));
+        EdgePattern::new(self.edge, p0, p1)
     }
 
     pub fn matches(self,
-                   puzzle: &Puzzle,
+                   board: &Board,
                    side_map: &mut SideMap)
                    -> SolverResult<MatchResult<EdgePattern<CellId>>> {
-        self.to_cellid(puzzle).matches(side_map)
+        self.to_cellid(board).matches(side_map)
     }
 }
 
@@ -6,12 +6,16 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
+use std::cmp;
+use std::collections::{HashMap, HashSet};
+
 use union_find::{QuickFindUf as Uf, UnionBySizeRank as Union, UnionFind};
-use srither_core::puzzle::{Edge, Puzzle, Side};
-use srither_core::geom::{CellId, Geom, Move};
+use srither_core::graph::Board;
+use srither_core::puzzle::{Edge, Marker, Side};
+use srither_core::geom::CellId;
 
-use SolverResult;
-use model::State;
+use {Error, SolverResult};
+use model::{self, State};
 
 pub trait Key {
     fn key0(self) -> usize;
@@ -74,13 +78,16 @@ impl Clone for SideMap {
 }
 
 impl SideMap {
-    pub fn new(puzzle: &Puzzle) -> SideMap {
-        let num_cell = puzzle.cell_len();
-        let max_revision = (puzzle.row() * puzzle.column()) as u32;
+    pub fn new(board: &Board) -> SideMap {
+        SideMap::with_cell_len(board.num_faces() + 1)
+    }
+
+    // `cell_len` includes the outside cell.
+    pub fn with_cell_len(cell_len: usize) -> SideMap {
         SideMap {
-            uf: UnionFind::new(num_cell * 2),
+            uf: UnionFind::new(cell_len * 2),
             revision: 0,
-            max_revision: max_revision,
+            max_revision: (cell_len - 1) as u32,
         }
     }
 
@@ -104,6 +111,41 @@ impl SideMap {
         }
     }
 
+    // Returns the smallest and the largest numbers of the cells which can be
+    // inside. The undecided cells on the same side are counted together, and
+    // so are the cells on the other side of them.
+    pub fn count_inside(&mut self) -> (usize, usize) {
+        let out = self.uf.find(OUTSIDE_KEY0);
+        let inside = self.uf.find(OUTSIDE_KEY1);
+
+        // The number of the cells and `key1` of one of them, for each root of
+        // `key0` of the undecided cells.
+        let mut groups = HashMap::new();
+        let mut num_inside = 0;
+        for i in 1..(self.uf.size() / 2) {
+            let p = CellId::new(i);
+            let a = self.uf.find(p.key0());
+            if a == inside {
+                num_inside += 1;
+            } else if a != out {
+                groups.entry(a).or_insert((0, p.key1())).0 += 1;
+            }
+        }
+
+        let (mut min, mut max) = (num_inside, num_inside);
+        let mut visited = HashSet::new();
+        for (&a, &(n, key1)) in &groups {
+            let b = self.uf.find(key1);
+            if !visited.insert(a) || !visited.insert(b) {
+                continue;
+            }
+            let m = groups.get(&b).map_or(0, |g| g.0);
+            min += cmp::min(n, m);
+            max += cmp::max(n, m);
+        }
+        (min, max)
+    }
+
     pub fn get_edge<T>(&mut self, p0: T, p1: T) -> State<Edge>
         where T: Key + Copy
     {
@@ -155,69 +197,90 @@ impl SideMap {
         }
     }
 
-    pub fn complete_puzzle(&mut self, puzzle: &mut Puzzle) -> SolverResult<()> {
-        for p in puzzle.points() {
-            let cp = puzzle.point_to_cellid(p);
-            let cp_u = puzzle.point_to_cellid(p + Move::UP);
-            let cp_l = puzzle.point_to_cellid(p + Move::LEFT);
-
-            puzzle.set_side(p, try!(self.get_side(cp).into()));
-            puzzle.set_edge_h(p, try!(self.get_edge(cp, cp_u).into()));
-            puzzle.set_edge_v(p, try!(self.get_edge(cp, cp_l).into()));
+    pub fn complete_board(&mut self, board: &mut Board) -> SolverResult<()> {
+        for f in 0..board.num_faces() {
+            let side = try!(self.get_side(model::face_cell(Some(f))).into());
+            board.set_side(f, side);
         }
-
-        for p in puzzle.points_in_column(puzzle.column()) {
-            let cp = puzzle.point_to_cellid(p);
-            let cp_l = puzzle.point_to_cellid(p + Move::LEFT);
-
-            puzzle.set_edge_v(p, try!(self.get_edge(cp, cp_l).into()));
-        }
-
-        for p in puzzle.points_in_row(puzzle.row()) {
-            let cp = puzzle.point_to_cellid(p);
-            let cp_u = puzzle.point_to_cellid(p + Move::UP);
-
-            puzzle.set_edge_h(p, try!(self.get_edge(cp, cp_u).into()));
+        for e in 0..board.num_edges() {
+            let (c0, c1) = model::edge_cells(board, e);
+            let edge = try!(self.get_edge(c0, c1).into());
+            board.set_edge(e, edge);
         }
         Ok(())
     }
-}
 
-impl<'a> From<&'a Puzzle> for SideMap {
-    fn from(puzzle: &'a Puzzle) -> SideMap {
-        let mut map = SideMap::new(puzzle);
-        for p in puzzle.points() {
-            let cp = puzzle.point_to_cellid(p);
-            let cp_u = puzzle.point_to_cellid(p + Move::UP);
-            let cp_l = puzzle.point_to_cellid(p + Move::LEFT);
-
-            if let Some(side) = puzzle.side(p) {
-                map.set_side(cp, side);
-            }
-            if let Some(edge) = puzzle.edge_h(p) {
-                map.set_edge(cp, cp_u, edge);
+    // The sides, the edges and the markers of the board may contradict each
+    // other, which leaves both keys of some cell in the same set.
+    pub fn from_board(board: &Board) -> SolverResult<SideMap> {
+        let mut map = SideMap::new(board);
+        for f in 0..board.num_faces() {
+            if let Some(side) = board.side(f) {
+                let _ = map.set_side(model::face_cell(Some(f)), side);
             }
-            if let Some(edge) = puzzle.edge_v(p) {
-                map.set_edge(cp, cp_l, edge);
+        }
+        for e in 0..board.num_edges() {
+            if let Some(edge) = board.edge(e) {
+                let (c0, c1) = model::edge_cells(board, e);
+                let _ = map.set_edge(c0, c1, edge);
             }
         }
 
-        for p in puzzle.points_in_column(puzzle.column()) {
-            let cp = puzzle.point_to_cellid(p);
-            let cp_l = puzzle.point_to_cellid(p + Move::LEFT);
-
-            if let Some(edge) = puzzle.edge_v(p) {
-                map.set_edge(cp, cp_l, edge);
+        // All the sheep are on the same side, and all the wolves are on the
+        // other side.
+        let first = |marker| (0..board.num_faces()).find(|&f| board.marker(f) == Some(marker));
+        let sheep = first(Marker::Sheep).map(|f| model::face_cell(Some(f)));
+        let wolf = first(Marker::Wolf).map(|f| model::face_cell(Some(f)));
+        for f in 0..board.num_faces() {
+            let cp = model::face_cell(Some(f));
+            match (board.marker(f), sheep, wolf) {
+                (Some(Marker::Sheep), Some(c), _) |
+                (Some(Marker::Wolf), _, Some(c)) => {
+                    let _ = map.set_same(cp, c);
+                }
+                _ => {}
             }
         }
-        for p in puzzle.points_in_row(puzzle.row()) {
-            let cp = puzzle.point_to_cellid(p);
-            let cp_u = puzzle.point_to_cellid(p + Move::UP);
+        if let (Some(s), Some(w)) = (sheep, wolf) {
+            let _ = map.set_different(s, w);
+        }
 
-            if let Some(edge) = puzzle.edge_h(p) {
-                map.set_edge(cp, cp_u, edge);
+        for i in 0..(board.num_faces() + 1) {
+            let p = CellId::new(i);
+            if map.uf.find(p.key0()) == map.uf.find(p.key1()) {
+                return Err(Error::invalid_board());
             }
         }
-        map
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use srither_core::geom::CellId;
+    use super::SideMap;
+
+    #[test]
+    fn count_inside() {
+        let mut map = SideMap::with_cell_len(8);
+        assert_eq!((0, 7), map.count_inside());
+
+        let _ = map.set_inside(CellId::new(1));
+        let _ = map.set_outside(CellId::new(2));
+        assert_eq!((1, 6), map.count_inside());
+
+        // The cells 3 and 4 are on the one side, and the cell 5 is on the
+        // other side of them.
+        let _ = map.set_same(CellId::new(3), CellId::new(4));
+        let _ = map.set_different(CellId::new(4), CellId::new(5));
+        assert_eq!((2, 5), map.count_inside());
+
+        // The groups on both sides are counted once.
+        let _ = map.set_same(CellId::new(5), CellId::new(6));
+        assert_eq!((3, 4), map.count_inside());
+
+        let _ = map.set_inside(CellId::new(7));
+        let _ = map.set_outside(CellId::new(5));
+        assert_eq!((4, 4), map.count_inside());
     }
 }
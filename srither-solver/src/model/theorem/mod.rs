@@ -8,8 +8,8 @@
 
 use std::iter::FromIterator;
 
-use srither_core::puzzle::Puzzle;
-use srither_core::geom::{CellId, Geom, Move, Point, Rotation, Size};
+use srither_core::graph::Board;
+use srither_core::geom::{CellId, Move, Point, Rotation, Size};
 
 use SolverResult;
 use model::SideMap;
@@ -116,7 +116,7 @@ impl Theorem {
     }
 
     fn can_close(shift: Move,
-                 puzzle: &Puzzle,
+                 board: &Board,
                  sum_of_hint: u32,
                  hpat: &[HintPattern],
                  sum_of_hpat: u32)
@@ -127,7 +127,7 @@ impl Theorem {
 
         let mut ava_sum = 0;
         for h in hpat {
-            if let Some(n) = puzzle.hint(h.point() + shift) {
+            if let Some(n) = board.face_at(h.point() + shift).and_then(|f| board.hint(f)) {
                 if n != h.hint() {
                     return false;
                 }
@@ -139,18 +139,30 @@ impl Theorem {
             return false;
         }
 
+        // The sets of the numbers may be the hints of the pattern, or 0 out of
+        // the pattern.
+        for f in 0..board.num_faces() {
+            if let Some(set) = board.hint_set(f) {
+                let p = board.face_point(f);
+                let n = hpat.iter().find(|h| h.point() + shift == p).map_or(0, |h| h.hint());
+                if !set.contains(n) {
+                    return false;
+                }
+            }
+        }
+
         true
     }
 
     pub fn shift_matches(&self,
                          shift: Move,
-                         puzzle: &Puzzle,
+                         board: &Board,
                          sum_of_hint: u32,
                          side_map: &mut SideMap)
                          -> SolverResult<MatchResult> {
         let mut num_matcher = 0;
         for matcher in &self.hint_matcher {
-            match try!(matcher.shift(shift).matches::<Point>(puzzle)) {
+            match try!(matcher.shift(shift).matches::<Point>(board)) {
                 PatternMatchResult::Complete => {}
                 PatternMatchResult::Conflict => {
                     return Ok(MatchResult::Conflict);
@@ -160,7 +172,7 @@ impl Theorem {
         }
 
         for matcher in &self.edge_matcher {
-            match try!(matcher.shift(shift).matches(puzzle, side_map)) {
+            match try!(matcher.shift(shift).matches(board, side_map)) {
                 PatternMatchResult::Complete => {}
                 PatternMatchResult::Partial(_) => {
                     num_matcher += 1;
@@ -172,14 +184,14 @@ impl Theorem {
         }
 
         if let Some((sum_of_hpat, ref hpat)) = self.closed_hint {
-            if Theorem::can_close(shift, puzzle, sum_of_hint, hpat, sum_of_hpat) {
+            if Theorem::can_close(shift, board, sum_of_hint, hpat, sum_of_hpat) {
                 return Ok(MatchResult::Conflict);
             }
         }
 
         let result = self.result
                          .iter()
-                         .map(|pat| pat.shift(shift).to_cellid(&puzzle))
+                         .map(|pat| pat.shift(shift).to_cellid(board))
                          .collect();
 
         if num_matcher == 0 {
@@ -188,7 +200,7 @@ impl Theorem {
 
         let mut new_matcher = Vec::with_capacity(num_matcher);
         for matcher in &self.edge_matcher {
-            match try!(matcher.shift(shift).matches(puzzle, side_map)) {
+            match try!(matcher.shift(shift).matches(board, side_map)) {
                 PatternMatchResult::Complete => {}
                 PatternMatchResult::Partial(m) => {
                     new_matcher.push(m);
@@ -211,6 +223,15 @@ pub struct PartialTheorem {
 }
 
 impl PartialTheorem {
+    pub fn new(matcher: Vec<EdgePattern<CellId>>,
+               result: Vec<EdgePattern<CellId>>)
+               -> PartialTheorem {
+        PartialTheorem {
+            matcher: matcher,
+            result: result,
+        }
+    }
+
     pub fn dummy() -> PartialTheorem {
         PartialTheorem {
             matcher: vec![],
@@ -9,11 +9,12 @@
 use std::collections::HashMap;
 use std::rc::Rc;
 use std::mem;
-use srither_core::geom::{CellId, Geom, Move};
-use srither_core::puzzle::{Edge, Puzzle};
+use srither_core::geom::{CellId, Move};
+use srither_core::graph::{Board, Tiling};
+use srither_core::puzzle::Edge;
 
 use {Error, SolverResult};
-use model::{KeyPair, SideMap, State};
+use model::{self, KeyPair, SideMap, State};
 use model::pattern::EdgePattern;
 use model::theorem::{MatchResult, PartialTheorem, Theorem};
 
@@ -49,13 +50,17 @@ impl Clone for TheoremPool {
 
 impl TheoremPool {
     pub fn new<T>(theo_defs: T,
-                  puzzle: &Puzzle,
+                  board: &Board,
                   sum_of_hint: u32,
                   side_map: &mut SideMap)
                   -> SolverResult<TheoremPool>
         where T: IntoIterator<Item = Theorem>
     {
-        let mut matchers = try!(create_matcher_list(theo_defs, puzzle, sum_of_hint, side_map));
+        let mut matchers = if board.tiling() == Tiling::Square {
+            try!(create_matcher_list(theo_defs, board, sum_of_hint, side_map))
+        } else {
+            try!(create_local_matcher_list(board, side_map))
+        };
 
         loop {
             let rev = side_map.revision();
@@ -169,7 +174,7 @@ impl TheoremPool {
 }
 
 fn create_matcher_list<T>(theo_defs: T,
-                          puzzle: &Puzzle,
+                          board: &Board,
                           sum_of_hint: u32,
                           side_map: &mut SideMap)
                           -> SolverResult<Vec<PartialTheorem>>
@@ -190,21 +195,23 @@ fn create_matcher_list<T>(theo_defs: T,
 
     let mut data = vec![];
 
-    for p in puzzle.points() {
-        if let Some(x) = puzzle.hint(p) {
+    for f in 0..board.num_faces() {
+        if let Some(x) = board.hint(f) {
+            let p = board.face_point(f);
             for theo in &hint_theorem[x as usize] {
                 let o = theo.head().unwrap().point();
-                try!(theo.shift_matches(p - o, puzzle, sum_of_hint, side_map))
+                try!(theo.shift_matches(p - o, board, sum_of_hint, side_map))
                     .update(side_map, &mut data);
             }
         }
     }
 
+    let size = board.size();
     for theo in nonhint_theorem {
         let sz = theo.size();
-        for r in (1 - sz.0)..(puzzle.row() + sz.0 - 1) {
-            for c in (1 - sz.1)..(puzzle.column() + sz.1 - 1) {
-                try!(theo.shift_matches(Move(r, c), puzzle, sum_of_hint, side_map))
+        for r in (1 - sz.0)..(size.0 + sz.0 - 1) {
+            for c in (1 - sz.1)..(size.1 + sz.1 - 1) {
+                try!(theo.shift_matches(Move(r, c), board, sum_of_hint, side_map))
                     .update(side_map, &mut data);
             }
         }
@@ -213,6 +220,93 @@ fn create_matcher_list<T>(theo_defs: T,
     Ok(data)
 }
 
+// All the ways to choose `n` of the items.
+fn combinations<T: Copy>(items: &[T], n: usize) -> Vec<Vec<T>> {
+    if n == 0 {
+        return vec![vec![]];
+    }
+    if items.len() < n {
+        return vec![];
+    }
+    let mut result = combinations(&items[1..], n - 1);
+    for comb in &mut result {
+        comb.insert(0, items[0]);
+    }
+    result.extend(combinations(&items[1..], n));
+    result
+}
+
+// The theorems are written on the square grid, so the boards of the other
+// tilings use the rules of each face and vertex instead. A face with the
+// hint `n` has `n` lines, and a vertex has no line or two lines.
+fn create_local_matcher_list(board: &Board,
+                             side_map: &mut SideMap)
+                             -> SolverResult<Vec<PartialTheorem>> {
+    let mut theorems = vec![];
+    let line = |e| {
+        let (c0, c1) = model::edge_cells(board, e);
+        EdgePattern::line(c0, c1)
+    };
+    let cross = |e| {
+        let (c0, c1) = model::edge_cells(board, e);
+        EdgePattern::cross(c0, c1)
+    };
+    // The edges other than the given ones become `edge`.
+    let others = |edges: &[usize], given: &[usize], pat: &Fn(usize) -> EdgePattern<CellId>| {
+        edges.iter()
+             .filter(|e| !given.contains(e))
+             .map(|&e| pat(e))
+             .collect::<Vec<_>>()
+    };
+
+    for f in 0..board.num_faces() {
+        let n = match board.hint(f) {
+            Some(n) => n as usize,
+            None => continue,
+        };
+        let edges = board.face_edges(f);
+        for lines in combinations(edges, n) {
+            let matcher = lines.iter().map(|&e| line(e)).collect();
+            theorems.push((matcher, others(edges, &lines, &cross)));
+        }
+        for crosses in combinations(edges, edges.len() - n) {
+            let matcher = crosses.iter().map(|&e| cross(e)).collect();
+            theorems.push((matcher, others(edges, &crosses, &line)));
+        }
+    }
+
+    for v in 0..board.num_vertices() {
+        let edges = board.vertex_edges(v);
+        for lines in combinations(edges, 2) {
+            let matcher = lines.iter().map(|&e| line(e)).collect();
+            theorems.push((matcher, others(edges, &lines, &cross)));
+        }
+        for crosses in combinations(edges, edges.len() - 1) {
+            let matcher = crosses.iter().map(|&e| cross(e)).collect();
+            theorems.push((matcher, others(edges, &crosses, &cross)));
+        }
+        // A line and crosses on all the edges but one.
+        for &e0 in edges {
+            for &e1 in edges.iter().filter(|&&e1| e1 != e0) {
+                let matcher = edges.iter()
+                                   .filter(|&&e| e != e1)
+                                   .map(|&e| if e == e0 { line(e) } else { cross(e) })
+                                   .collect();
+                theorems.push((matcher, vec![line(e1)]));
+            }
+        }
+    }
+
+    let mut data = vec![];
+    for (matcher, result) in theorems {
+        if !result.is_empty() {
+            try!(PartialTheorem::new(matcher, result).matches(side_map))
+                .update(side_map, &mut data);
+        }
+    }
+    Ok(data)
+}
+
 fn apply_all_theorem(matchers: &mut Vec<PartialTheorem>,
                      side_map: &mut SideMap)
                      -> SolverResult<()> {
@@ -6,15 +6,18 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use srither_core::puzzle::{Puzzle, Side};
-use srither_core::geom::{CellId, Geom};
+use std::rc::Rc;
+use srither_core::graph::Board;
+use srither_core::puzzle::{Edge, Side};
+use srither_core::geom::CellId;
 
 use {Error, SolverResult};
-use model::{ConnectMap, SideMap, State, Theorem, TheoremPool};
+use model::{self, ConnectMap, SideMap, State, Theorem, TheoremPool};
 
 #[derive(Debug)]
-pub struct Solver<'a> {
-    puzzle: &'a Puzzle,
+pub struct Solver {
+    board: Rc<Board>,
+    edges: Rc<Vec<(CellId, CellId)>>,
     sum_of_hint: u32,
     theorem_pool: TheoremPool,
     side_map: SideMap,
@@ -22,10 +25,11 @@ pub struct Solver<'a> {
     conn_analysis_revision: Option<u32>,
 }
 
-impl<'a> Clone for Solver<'a> {
-    fn clone(&self) -> Solver<'a> {
+impl Clone for Solver {
+    fn clone(&self) -> Solver {
         Solver {
-            puzzle: self.puzzle,
+            board: self.board.clone(),
+            edges: self.edges.clone(),
             sum_of_hint: self.sum_of_hint,
             theorem_pool: self.theorem_pool.clone(),
             side_map: self.side_map.clone(),
@@ -34,8 +38,9 @@ impl<'a> Clone for Solver<'a> {
         }
     }
 
-    fn clone_from(&mut self, other: &Solver<'a>) {
-        self.puzzle = other.puzzle;
+    fn clone_from(&mut self, other: &Solver) {
+        self.board.clone_from(&other.board);
+        self.edges.clone_from(&other.edges);
         self.sum_of_hint = other.sum_of_hint;
         self.theorem_pool.clone_from(&other.theorem_pool);
         self.side_map.clone_from(&other.side_map);
@@ -44,22 +49,28 @@ impl<'a> Clone for Solver<'a> {
     }
 }
 
-impl<'a> Solver<'a> {
-    pub fn new<I>(puzzle: &'a Puzzle, theorem: I) -> SolverResult<Solver<'a>>
+impl Solver {
+    pub fn new<I>(board: Rc<Board>, theorem: I) -> SolverResult<Solver>
         where I: Iterator<Item = Theorem>
     {
         let mut sum_of_hint = 0;
-        for p in puzzle.points() {
-            if let Some(n) = puzzle.hint(p) {
+        for f in 0..board.num_faces() {
+            if let Some(n) = board.hint(f) {
+                // A face cannot have more lines than its edges.
+                if n as usize > board.face_edges(f).len() {
+                    return Err(Error::invalid_board());
+                }
                 sum_of_hint += n as u32;
             }
         }
 
-        let mut side_map = SideMap::from(puzzle);
-        let pool = try!(TheoremPool::new(theorem, puzzle, sum_of_hint, &mut side_map));
+        let mut side_map = try!(SideMap::from_board(&board));
+        let pool = try!(TheoremPool::new(theorem, &board, sum_of_hint, &mut side_map));
+        let edges = (0..board.num_edges()).map(|e| model::edge_cells(&board, e)).collect();
 
         Ok(Solver {
-            puzzle: puzzle,
+            board: board,
+            edges: Rc::new(edges),
             sum_of_hint: sum_of_hint,
             theorem_pool: pool,
             side_map: side_map,
@@ -86,10 +97,35 @@ impl<'a> Solver<'a> {
     }
 
     pub fn validate_result(&mut self) -> SolverResult<()> {
+        try!(self.apply_hint_sets());
+        try!(self.apply_vertex_hints());
+        try!(self.apply_loop_constraints());
         try!(self.sync_connection());
         if self.connect_map().count_area() != 2 {
             return Err(Error::invalid_board());
         }
+        self.check_loop()
+    }
+
+    // The theorems may not be applied after the last cells are decided, so
+    // the hints and the loop are checked again.
+    fn check_loop(&mut self) -> SolverResult<()> {
+        let board = self.board.clone();
+        let mut lines = vec![false; board.num_edges()];
+        for (line, &(c0, c1)) in lines.iter_mut().zip(self.edges.iter()) {
+            let edge: Option<Edge> = try!(self.side_map.get_edge(c0, c1).into());
+            *line = edge == Some(Edge::Line);
+        }
+        for f in 0..board.num_faces() {
+            if let Some(n) = board.hint(f) {
+                if board.face_edges(f).iter().filter(|&&e| lines[e]).count() != n as usize {
+                    return Err(Error::invalid_board());
+                }
+            }
+        }
+        if board.loop_path(&lines).is_none() {
+            return Err(Error::invalid_board());
+        }
         Ok(())
     }
 
@@ -113,6 +149,15 @@ impl<'a> Solver<'a> {
     pub fn apply_all_theorem(&mut self) -> SolverResult<()> {
         self.theorem_pool.apply_all(&mut self.side_map)
     }
+    pub fn apply_hint_sets(&mut self) -> SolverResult<()> {
+        ::step::hint_set::run(&self.board, &mut self.side_map)
+    }
+    pub fn apply_vertex_hints(&mut self) -> SolverResult<()> {
+        ::step::vertex_hint::run(&self.board, &mut self.side_map)
+    }
+    pub fn apply_loop_constraints(&mut self) -> SolverResult<()> {
+        ::step::loop_constraint::run(&self.board, &self.edges, &mut self.side_map)
+    }
     pub fn connect_analysis(&mut self) -> SolverResult<()> {
         self.create_connect_map();
         ::step::connect_analysis::run(&mut self.side_map,
@@ -121,8 +166,8 @@ impl<'a> Solver<'a> {
     }
 
     pub fn mark_common(&mut self, s0: &mut Solver, s1: &mut Solver) {
-        for i in 0..self.puzzle.cell_len() {
-            let p = CellId::new(i);
+        for f in 0..self.board.num_faces() {
+            let p = model::face_cell(Some(f));
             if let State::Fixed(side) = s0.side_map.get_side(p) {
                 if s1.side_map.get_side(p) == State::Fixed(side) {
                     self.side_map.set_side(p, side);
@@ -130,19 +175,7 @@ impl<'a> Solver<'a> {
             }
         }
 
-        for i in 0..(self.puzzle.cell_len() - 1) {
-            let p0 = CellId::new(i);
-            let p1 = CellId::new(i + 1);
-            if let State::Fixed(side) = s0.side_map.get_edge(p0, p1) {
-                if s1.side_map.get_edge(p0, p1) == State::Fixed(side) {
-                    self.side_map.set_edge(p0, p1, side);
-                }
-            }
-        }
-
-        for i in 0..(self.puzzle.cell_len() - (self.puzzle.column() as usize)) {
-            let p0 = CellId::new(i);
-            let p1 = CellId::new(i + (self.puzzle.column() as usize));
+        for &(p0, p1) in self.edges.iter() {
             if let State::Fixed(side) = s0.side_map.get_edge(p0, p1) {
                 if s1.side_map.get_edge(p0, p1) == State::Fixed(side) {
                     self.side_map.set_edge(p0, p1, side);
@@ -153,7 +186,7 @@ impl<'a> Solver<'a> {
 
     fn create_connect_map(&mut self) {
         if self.connect_map.is_none() {
-            let conn_map = ConnectMap::new(self.puzzle, &mut self.side_map);
+            let conn_map = ConnectMap::new(&self.board, &mut self.side_map);
             self.connect_map = Some(conn_map);
         }
     }
@@ -176,10 +209,10 @@ impl<'a> Solver<'a> {
     // }
 }
 
-impl<'a> Into<SolverResult<Puzzle>> for Solver<'a> {
-    fn into(mut self) -> SolverResult<Puzzle> {
-        let mut puzzle = self.puzzle.clone();
-        try!(self.side_map.complete_puzzle(&mut puzzle));
-        Ok(puzzle)
+impl Into<SolverResult<Board>> for Solver {
+    fn into(mut self) -> SolverResult<Board> {
+        let mut board = (*self.board).clone();
+        try!(self.side_map.complete_board(&mut board));
+        Ok(board)
     }
 }
@@ -0,0 +1,54 @@
+// Copyright (c) 2016 srither-solver developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+// The theorems match the exact hints only, so the cells with the sets of the
+// numbers are filled here instead.
+
+use srither_core::graph::Board;
+use srither_core::puzzle::Edge;
+
+use {Error, SolverResult};
+use model::{self, SideMap, State};
+
+pub fn run(board: &Board, side_map: &mut SideMap) -> SolverResult<()> {
+    for f in 0..board.num_faces() {
+        let set = match board.hint_set(f) {
+            Some(set) => set,
+            None => continue,
+        };
+
+        let mut lines = 0;
+        let mut unknown = vec![];
+        for &e in board.face_edges(f) {
+            let (c0, c1) = model::edge_cells(board, e);
+            match side_map.get_edge(c0, c1) {
+                State::Fixed(Edge::Line) => lines += 1,
+                State::Fixed(Edge::Cross) => {}
+                State::Unknown => unknown.push((c0, c1)),
+                State::Conflict => return Err(Error::invalid_board()),
+            }
+        }
+
+        // The numbers which are still possible.
+        let nums = set.values()
+                      .into_iter()
+                      .filter(|&n| lines <= n && n <= lines + unknown.len() as u8)
+                      .collect::<Vec<_>>();
+        let edge = match (nums.first(), nums.last()) {
+            (None, _) | (_, None) => return Err(Error::invalid_board()),
+            (_, Some(&max)) if max == lines => Edge::Cross,
+            (Some(&min), _) if min == lines + unknown.len() as u8 => Edge::Line,
+            _ => continue,
+        };
+        for (c0, c1) in unknown {
+            let _ = side_map.set_edge(c0, c1, edge);
+        }
+    }
+
+    Ok(())
+}
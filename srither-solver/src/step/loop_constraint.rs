@@ -0,0 +1,63 @@
+// Copyright (c) 2016 srither-solver developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+// The length and the area of the loop, if the puzzle gives them. The area
+// only prunes the boards which cannot have the given number of the cells
+// inside, while the length also fills the edges when the lines are enough.
+
+use srither_core::geom::CellId;
+use srither_core::graph::{Board, Tiling};
+use srither_core::puzzle::Edge;
+
+use {Error, SolverResult};
+use model::{SideMap, State};
+
+// `edges` are the cells on both sides of each edge of the board.
+pub fn run(board: &Board, edges: &[(CellId, CellId)], side_map: &mut SideMap) -> SolverResult<()> {
+    if let Some(area) = board.area() {
+        let (min, max) = side_map.count_inside();
+        if (area as usize) < min || max < (area as usize) {
+            return Err(Error::invalid_board());
+        }
+    }
+
+    if let Some(n) = board.loop_length() {
+        let n = n as usize;
+        // Any loop on the square grid has an even length.
+        if board.tiling() == Tiling::Square && n % 2 == 1 {
+            return Err(Error::invalid_board());
+        }
+
+        let mut lines = 0;
+        let mut unknown = vec![];
+        for &(c0, c1) in edges {
+            match side_map.get_edge(c0, c1) {
+                State::Fixed(Edge::Line) => lines += 1,
+                State::Fixed(Edge::Cross) => {}
+                State::Unknown => unknown.push((c0, c1)),
+                State::Conflict => return Err(Error::invalid_board()),
+            }
+        }
+
+        if lines > n || lines + unknown.len() < n {
+            return Err(Error::invalid_board());
+        }
+        let edge = if lines == n {
+            Edge::Cross
+        } else if lines + unknown.len() == n {
+            Edge::Line
+        } else {
+            return Ok(());
+        };
+        for (c0, c1) in unknown {
+            let _ = side_map.set_edge(c0, c1, edge);
+        }
+    }
+
+    Ok(())
+}
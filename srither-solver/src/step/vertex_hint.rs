@@ -0,0 +1,50 @@
+// Copyright (c) 2016 srither-solver developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use srither_core::graph::Board;
+use srither_core::puzzle::Edge;
+
+use {Error, SolverResult};
+use model::{self, SideMap, State};
+
+pub fn run(board: &Board, side_map: &mut SideMap) -> SolverResult<()> {
+    for v in 0..board.num_vertices() {
+        let n = match board.vertex_hint(v) {
+            Some(n) => n as usize,
+            None => continue,
+        };
+
+        let mut lines = 0;
+        let mut unknown = vec![];
+        for &e in board.vertex_edges(v) {
+            let (c0, c1) = model::edge_cells(board, e);
+            match side_map.get_edge(c0, c1) {
+                State::Fixed(Edge::Line) => lines += 1,
+                State::Fixed(Edge::Cross) => {}
+                State::Unknown => unknown.push((c0, c1)),
+                State::Conflict => return Err(Error::invalid_board()),
+            }
+        }
+
+        if lines > n || lines + unknown.len() < n {
+            return Err(Error::invalid_board());
+        }
+        let edge = if lines == n {
+            Edge::Cross
+        } else if lines + unknown.len() == n {
+            Edge::Line
+        } else {
+            continue;
+        };
+        for (c0, c1) in unknown {
+            let _ = side_map.set_edge(c0, c1, edge);
+        }
+    }
+
+    Ok(())
+}